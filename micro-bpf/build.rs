@@ -1,5 +1,532 @@
+extern crate alloc;
+
+use std::path::PathBuf;
+
+// The baseline parser is shared with the crate, whose host tests cover it
+#[path = "src/infra/baseline_file.rs"]
+mod baseline_file;
+
+/// Start of a Femto-Container header.
+const FEMTO_CONTAINER_MAGIC: &[u8] = b"rBPF";
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+
 fn main() {
     println!("cargo:rerun-if-env-changed=ITERATIONS");
     let iterations = std::env::var("ITERATIONS").expect("ITERATIONS not set");
     println!("cargo:rustc-env=ITERATIONS={}", iterations);
+    forward_env("WARMUP", "0");
+    forward_env("WARMUP_WINDOW", "5");
+    forward_env("WARMUP_SPREAD_PCT", "5");
+    forward_env("WARMUP_MAX", "100");
+    forward_env("SANITY_MIN_US", "1");
+    forward_env("REGRESSION_THRESHOLD_PCT", "5");
+    forward_env("REGRESSION_GATE", "0");
+    forward_env("DRIFT_THRESHOLD_PCT", "0");
+    forward_env("DRIFT_WINDOW", "10");
+    forward_env("PERIOD_MS", "10");
+    forward_env("CONCURRENT_VMS", "1");
+    forward_env("REGION_COUNT", "0");
+    forward_env("ROBUST", "0");
+    forward_env("RESTART_ON_EARLY_FAILURE", "0");
+    forward_env("MAX_RESTARTS", "3");
+    forward_env("HELPER_PATTERN", "10");
+    forward_env("EXEC_BATCH", "1");
+    forward_env("VM_KIND", "mbuff");
+    forward_env("MAX_ITERATIONS", "100000");
+    forward_env("TRIGGER_PERIOD_MS", "0");
+    forward_env("MEMBW_EVERY", "10");
+    forward_env("REDUCE", "all");
+    forward_env("LABEL_COLUMN", "0");
+    forward_env("SUCCESS_MODE", "expected");
+    forward_env("EXPECTED", "1");
+    forward_env("ARG_ENDIAN", "native");
+    if std::env::var_os("CARGO_FEATURE_REFERENCE").is_some() {
+        forward_env("SCALE_FACTOR", "1");
+    }
+    forward_run_label();
+
+    generate_baseline();
+    forward_rbpf_version();
+
+    if std::env::var_os("CARGO_FEATURE_PMU").is_some() {
+        forward_env("PMU_PRESENT", "0");
+        generate_pmu_events();
+    }
+
+    if std::env::var_os("CARGO_FEATURE_TIMER_SELFTEST").is_some() {
+        forward_env("TIMER_SELFTEST_US", "1000");
+        forward_env("TIMER_SELFTEST_TOLERANCE_PCT", "10");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_HELPER_BENCH").is_some() {
+        forward_env("HELPER_BENCH_ID", "");
+        forward_env("HELPER_BENCH_CALLS", "100000");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_NO_DCACHE").is_some() {
+        forward_env("DCACHE_PRESENT", "0");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_SEEDS").is_some() {
+        forward_env("SEEDS", "");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_MBUFF_GUARD").is_some() {
+        generate_guard();
+    }
+
+    if std::env::var_os("CARGO_FEATURE_SUITE").is_some() {
+        forward_env("BASELINE_PROGRAM", "");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_JIT_TIMEOUT").is_some() {
+        forward_env("JIT_COMPILE_TIMEOUT_MS", "1000");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_CONFIDENCE").is_some() {
+        forward_env("CONFIDENCE_PCT", "2");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_TOP_SLOWEST").is_some() {
+        forward_env("TOP_SLOWEST", "5");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_PREWARM_MBUFF").is_some() {
+        forward_env("MBUFF_PREWARM", "1");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_RESERVE_HEAP").is_some() {
+        forward_env("RESERVE_HEAP_BYTES", "0");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_LOAD_THROUGHPUT").is_some() {
+        forward_env("LOAD_THROUGHPUT_COUNT", "1000");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_STOP_AT_INSN").is_some() {
+        forward_env("STOP_AT_INSN", "1");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_COAP_REPORT").is_some() {
+        forward_env("COAP_REPORT_URI", "coap://[2001:db8::1]/results");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_BURST").is_some() {
+        forward_env("BURST_SIZE", "10");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_STREAM").is_some() {
+        forward_env("STREAM_TIMEOUT_MS", "1000");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_ALIGN_PROG").is_some() {
+        forward_env("PROG_ALIGN", "8");
+        forward_env("PROG_OFFSET", "0");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_DUMP_CONTEXT").is_some() {
+        forward_env("DUMP_CONTEXT", "first");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_RESET_PERIPHERALS").is_some() {
+        forward_env("RESET_PERIPHERALS", "");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_FRAGMENT_HEAP").is_some() {
+        forward_env("FRAGMENT_HEAP_KB", "8");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_DUAL_UART").is_some() {
+        forward_env("DATA_UART", "1");
+        forward_env("DATA_UART_BAUD", "115200");
+    }
+
+    let jit = std::env::var_os("CARGO_FEATURE_JIT").is_some();
+    let header_cost = std::env::var_os("CARGO_FEATURE_HEADER_COST").is_some();
+
+    let programs = if std::env::var_os("CARGO_FEATURE_SUITE").is_some() {
+        generate_suite(jit)
+    } else if std::env::var_os("CARGO_FEATURE_BASE64_LOAD").is_some() {
+        // The program is received over stdio at runtime
+        Vec::new()
+    } else {
+        embed_program(jit, header_cost)
+    };
+
+    if std::env::var_os("CARGO_FEATURE_ENCODINGS").is_some() {
+        generate_encodings();
+    }
+
+    if std::env::var_os("CARGO_FEATURE_SIGNED_PROGRAMS").is_some() {
+        generate_signatures(&programs);
+    }
+
+    if std::env::var_os("CARGO_FEATURE_VFS_HELPERS").is_some() {
+        check_vfs_checksum();
+    }
+
+    // The self-test always runs on the interpreter, also in JIT builds
+    if std::env::var_os("CARGO_FEATURE_SELFTEST").is_some() {
+        let path = program_path("SELFTEST_BIN_PATH", "selftest.bin");
+        check_header_program(&path, "Build it with `make selftest.bin`, set SELFTEST_BIN_PATH or disable it with SELFTEST=skip.");
+        println!("cargo:rustc-env=SELFTEST_BIN_PATH={}", path.display());
+    }
+}
+
+/// Validates the single program under benchmark and exposes its location.
+/// Returns the path of the program loaded by the VM.
+fn embed_program(jit: bool, header_cost: bool) -> Vec<PathBuf> {
+    let mut loaded = Vec::new();
+    // The interpreter loads the program with the Femto-Container header, the
+    // JIT compiles the raw object file. header-cost needs both.
+    if !jit || header_cost {
+        let path = program_path("BENCH_BIN_PATH", "benchmark.bin");
+        check_header_program(&path, "Build it with `make benchmark.bin` or set BENCH_BIN_PATH to its location.");
+        println!("cargo:rustc-env=BENCH_BIN_PATH={}", path.display());
+        if !jit {
+            loaded.push(path);
+        }
+    }
+    if jit || header_cost {
+        let path = program_path("BENCH_OBJ_PATH", "benchmark.o");
+        check_object_program(&path, "Build it with `make benchmark.o` or set BENCH_OBJ_PATH to its location.");
+        println!("cargo:rustc-env=BENCH_OBJ_PATH={}", path.display());
+        if jit {
+            loaded.push(path);
+        }
+    }
+    loaded
+}
+
+/// Writes the public key given in `PUBLIC_KEY` and the signature of each
+/// program, read from `<program>.sig`, to `$OUT_DIR/signatures.rs`. Both are
+/// raw bytes as written by scripts/sign_program.py.
+fn generate_signatures(programs: &[PathBuf]) {
+    println!("cargo:rerun-if-env-changed=PUBLIC_KEY");
+    let key_path = match std::env::var_os("PUBLIC_KEY") {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("signing.pub"),
+    };
+    println!("cargo:rerun-if-changed={}", key_path.display());
+    let hint = "Create a key pair with `scripts/sign_program.py keygen` or set PUBLIC_KEY to the public key.";
+    let key = std::fs::read(&key_path).unwrap_or_else(|e| fail(&format!("cannot read public key {} ({}). {}", key_path.display(), e, hint)));
+    if key.len() != 32 {
+        fail(&format!("{} is not a raw Ed25519 public key, it has {} instead of 32 bytes.", key_path.display(), key.len()));
+    }
+
+    let mut table = format!("pub const PUBLIC_KEY: [u8; 32] = {:?};\n", key);
+    table.push_str("pub const SIGNATURES: &[ProgramSignature] = &[\n");
+    for program in programs {
+        let path = PathBuf::from(format!("{}.sig", program.display()));
+        println!("cargo:rerun-if-changed={}", path.display());
+        let signature = std::fs::read(&path).unwrap_or_else(|e| {
+            fail(&format!("cannot read signature {} ({}). Sign the program with `scripts/sign_program.py sign`.", path.display(), e))
+        });
+        if signature.len() != 64 {
+            fail(&format!("{} is not a raw Ed25519 signature, it has {} instead of 64 bytes.", path.display(), signature.len()));
+        }
+        table.push_str(&format!(
+            "    ProgramSignature {{ program: include_bytes!({:?}), signature: *include_bytes!({:?}) }},\n",
+            program.display().to_string(),
+            path.display().to_string()
+        ));
+    }
+    table.push_str("];\n");
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("signatures.rs");
+    std::fs::write(out, table).expect("failed to write signature table");
+}
+
+/// Fails if `VFS_CHECKSUM` from the Makefile doesn't match vfs/test.txt,
+/// ebpf/vfs_read.c would then report every iteration as incorrect.
+fn check_vfs_checksum() {
+    println!("cargo:rerun-if-env-changed=VFS_CHECKSUM");
+    let path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("vfs/test.txt");
+    println!("cargo:rerun-if-changed={}", path.display());
+    let bytes = std::fs::read(&path).unwrap_or_else(|e| fail(&format!("cannot read {} ({}).", path.display(), e)));
+    let checksum = bytes
+        .iter()
+        .fold(0x811c_9dc5u32, |hash, byte| (hash ^ *byte as u32).wrapping_mul(0x0100_0193));
+    let expected = std::env::var("VFS_CHECKSUM").unwrap_or_default();
+    if u32::from_str_radix(expected.trim_start_matches("0x"), 16).ok() != Some(checksum) {
+        fail(&format!("VFS_CHECKSUM={} doesn't match {}, set it to {:#010x} in the Makefile.", expected, path.display(), checksum));
+    }
+}
+
+/// Writes the event ids listed in `PMU_EVENTS` (default: instructions retired
+/// and cycles) and their column names to `$OUT_DIR/pmu.rs`.
+fn generate_pmu_events() {
+    println!("cargo:rerun-if-env-changed=PMU_EVENTS");
+    let events = std::env::var("PMU_EVENTS").unwrap_or_else(|_| "0x0008 0x0011".to_string());
+    let ids: Vec<u16> = events
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            let parsed = match id.strip_prefix("0x") {
+                Some(hex) => u16::from_str_radix(hex, 16),
+                None => id.parse(),
+            };
+            parsed.unwrap_or_else(|_| fail(&format!("PMU_EVENTS contains {}, which is not an event id.", id)))
+        })
+        .collect();
+    if ids.is_empty() || ids.len() > 4 {
+        fail(&format!("PMU_EVENTS lists {} events, the PMU is configured with 1 to 4.", ids.len()));
+    }
+
+    let columns: Vec<String> = ids.iter().map(|id| format!("pmu_{:#06x}", id)).collect();
+    let table = format!("pub const EVENTS: &[u16] = &{:?};\npub const COLUMNS: &[&str] = &{:?};\n", ids, columns);
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("pmu.rs");
+    std::fs::write(out, table).expect("failed to write PMU event table");
+}
+
+/// Writes the length of the guard after the program's memory, given in
+/// `MBUFF_GUARD_BYTES`, to `$OUT_DIR/guard.rs`. It sizes an array, it can't
+/// be parsed at runtime like the other settings.
+fn generate_guard() {
+    println!("cargo:rerun-if-env-changed=MBUFF_GUARD_BYTES");
+    let bytes = std::env::var("MBUFF_GUARD_BYTES").unwrap_or_else(|_| "16".to_string());
+    let bytes: usize = bytes
+        .trim()
+        .parse()
+        .unwrap_or_else(|_| fail(&format!("MBUFF_GUARD_BYTES is {}, not a number of bytes.", bytes)));
+    if bytes == 0 {
+        fail("MBUFF_GUARD_BYTES is 0, build without mbuff-guard instead.");
+    }
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("guard.rs");
+    std::fs::write(out, format!("pub const GUARD_BYTES: usize = {};\n", bytes)).expect("failed to write guard length");
+}
+
+/// Writes the per-program mean timings of the baseline file given in
+/// `BASELINE` to `$OUT_DIR/baseline.rs`. Without a baseline the table is empty.
+fn generate_baseline() {
+    println!("cargo:rerun-if-env-changed=BASELINE");
+    let mut table = String::from("pub const BASELINE: &[BaselineEntry] = &[\n");
+    match std::env::var("BASELINE") {
+        Ok(path) if !path.is_empty() => {
+            println!("cargo:rerun-if-changed={}", path);
+            let text = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| fail(&format!("cannot read baseline {} ({}).", path, e)));
+            let means = baseline_file::parse_baseline(&text).unwrap_or_else(|e| fail(&format!("malformed baseline {}: {}", path, e)));
+            for (program, load_program_us, execution_time_us) in means {
+                table.push_str(&format!(
+                    "    BaselineEntry {{ program: {:?}, load_program_us: {}, execution_time_us: {} }},\n",
+                    program, load_program_us, execution_time_us
+                ));
+            }
+        }
+        _ => {}
+    }
+    table.push_str("];\n");
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("baseline.rs");
+    std::fs::write(out, table).expect("failed to write baseline table");
+}
+
+/// Forwards the rbpf version resolved in Cargo.lock as `RBPF_VERSION`. rbpf is
+/// a path dependency, so the commit of its checkout is added if it's a git
+/// repository, e.g. "0.3.0 (git 1a2b3c4-dirty)".
+fn forward_rbpf_version() {
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let lockfile = manifest_dir.join("Cargo.lock");
+    let vm_dir = manifest_dir.join("../external/vm");
+    println!("cargo:rerun-if-changed={}", lockfile.display());
+    println!("cargo:rerun-if-changed={}", vm_dir.display());
+
+    let lock = std::fs::read_to_string(&lockfile).unwrap_or_default();
+    let mut version = lock
+        .split("[[package]]")
+        .find(|package| package.lines().any(|line| line.trim() == "name = \"rbpf\""))
+        .and_then(|package| package.lines().find_map(|line| line.trim().strip_prefix("version = ")))
+        .map(|version| version.trim_matches('"').to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    // Without its own .git (e.g. the submodule isn't checked out), git would
+    // describe the enclosing repository instead
+    if vm_dir.join(".git").exists() {
+        let describe = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&vm_dir)
+            .args(&["describe", "--always", "--dirty", "--abbrev=12"])
+            .output();
+        if let Some(output) = describe.ok().filter(|output| output.status.success()) {
+            version.push_str(&format!(" (git {})", String::from_utf8_lossy(&output.stdout).trim()));
+        }
+    }
+    println!("cargo:rustc-env=RBPF_VERSION={}", version);
+}
+
+/// Forwards `RUN_LABEL` verbatim. It ends up in the metadata and optionally
+/// in every row, so it must not contain the separator or a line break.
+fn forward_run_label() {
+    println!("cargo:rerun-if-env-changed=RUN_LABEL");
+    let label = std::env::var("RUN_LABEL").unwrap_or_default();
+    if label.contains(|c: char| c == ';' || c == '\n' || c == '\r') {
+        fail(&format!("RUN_LABEL={:?} contains a ';' or a line break, which would break the output.", label));
+    }
+    println!("cargo:rustc-env=RUN_LABEL={}", label);
+}
+
+/// Forwards an optional configuration variable to the crate, using the
+/// default when it isn't set.
+fn forward_env(name: &str, default: &str) {
+    println!("cargo:rerun-if-env-changed={}", name);
+    let value = std::env::var(name).unwrap_or_else(|_| default.to_string());
+    println!("cargo:rustc-env={}={}", name, value);
+}
+
+/// Writes the table of suite programs to `$OUT_DIR/suite.rs`. The programs
+/// listed in `SUITE` are expected in `SUITE_DIR` (default: `suite/`), named
+/// after the benchmark.
+fn generate_suite(jit: bool) -> Vec<PathBuf> {
+    println!("cargo:rerun-if-env-changed=SUITE");
+    println!("cargo:rerun-if-env-changed=SUITE_DIR");
+    let suite = std::env::var("SUITE").unwrap_or_default();
+    let names: Vec<&str> = suite.split(|c: char| c == ',' || c.is_whitespace()).filter(|n| !n.is_empty()).collect();
+    if names.is_empty() {
+        fail("the suite feature is enabled but SUITE lists no programs.");
+    }
+
+    let dir = match std::env::var_os("SUITE_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("suite"),
+    };
+
+    let mut table = String::from("pub const PROGRAMS: &[Program] = &[\n");
+    let mut paths = Vec::new();
+    for name in names {
+        let extension = if jit { "o" } else { "bin" };
+        let path = dir.join(format!("{}.{}", name, extension));
+        println!("cargo:rerun-if-changed={}", path.display());
+        let hint = "Build the suite with `make suite` or set SUITE_DIR to its location.";
+        if jit {
+            check_object_program(&path, hint);
+        } else {
+            check_header_program(&path, hint);
+        }
+        table.push_str(&format!(
+            "    Program {{ name: {:?}, bytes: include_bytes!({:?}) }},\n",
+            name,
+            path.display().to_string()
+        ));
+        paths.push(path);
+    }
+    table.push_str("];\n");
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("suite.rs");
+    std::fs::write(out, table).expect("failed to write suite table");
+    paths
+}
+
+/// Writes the encodings of each program to `$OUT_DIR/encodings.rs`: the
+/// Femto-Container binary and the raw object file, of the program under
+/// benchmark or of each program of `SUITE`. Missing encodings are left out,
+/// a program needs at least one.
+fn generate_encodings() {
+    let programs: Vec<(String, PathBuf, PathBuf)> = if std::env::var_os("CARGO_FEATURE_SUITE").is_some() {
+        println!("cargo:rerun-if-env-changed=SUITE");
+        println!("cargo:rerun-if-env-changed=SUITE_DIR");
+        let suite = std::env::var("SUITE").unwrap_or_default();
+        let dir = match std::env::var_os("SUITE_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("suite"),
+        };
+        suite
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|n| !n.is_empty())
+            .map(|name| {
+                let header = dir.join(format!("{}.bin", name));
+                let raw = dir.join(format!("{}.o", name));
+                println!("cargo:rerun-if-changed={}", header.display());
+                println!("cargo:rerun-if-changed={}", raw.display());
+                (name.to_string(), header, raw)
+            })
+            .collect()
+    } else {
+        vec![(
+            "benchmark".to_string(),
+            program_path("BENCH_BIN_PATH", "benchmark.bin"),
+            program_path("BENCH_OBJ_PATH", "benchmark.o"),
+        )]
+    };
+
+    let hint = "Build both encodings, e.g. with `make benchmark.bin benchmark.o`.";
+    let embed = |path: &PathBuf| {
+        if path.exists() {
+            format!("Some(include_bytes!({:?}))", path.display().to_string())
+        } else {
+            "None".to_string()
+        }
+    };
+    let mut table = String::from("pub const PROGRAMS: &[Encodings] = &[\n");
+    for (name, header, raw) in programs {
+        if !header.exists() && !raw.exists() {
+            fail(&format!("neither {} nor {} exists. {}", header.display(), raw.display(), hint));
+        }
+        if header.exists() {
+            check_header_program(&header, hint);
+        }
+        if raw.exists() {
+            check_object_program(&raw, hint);
+        }
+        table.push_str(&format!(
+            "    Encodings {{ name: {:?}, header: {}, raw: {} }},\n",
+            name,
+            embed(&header),
+            embed(&raw)
+        ));
+    }
+    table.push_str("];\n");
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("encodings.rs");
+    std::fs::write(out, table).expect("failed to write encodings table");
+}
+
+/// Resolves the location of a program file, either from the override
+/// environment variable or relative to the crate root.
+fn program_path(env_var: &str, default_name: &str) -> PathBuf {
+    println!("cargo:rerun-if-env-changed={}", env_var);
+    let path = match std::env::var_os(env_var) {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join(default_name),
+    };
+    println!("cargo:rerun-if-changed={}", path.display());
+    path
+}
+
+/// Reads a program file, `hint` tells the user how to provide it.
+fn read_program(path: &PathBuf, hint: &str) -> Vec<u8> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => fail(&format!("cannot read program file {} ({}). {}", path.display(), e, hint)),
+    };
+    if bytes.is_empty() {
+        fail(&format!("program file {} is empty. {}", path.display(), hint));
+    }
+    bytes
+}
+
+fn check_header_program(path: &PathBuf, hint: &str) {
+    let bytes = read_program(path, hint);
+    if bytes.starts_with(ELF_MAGIC) {
+        fail(&format!(
+            "{} is an ELF object file, but the interpreter expects a Femto-Container binary (.bin). \
+             Did you embed the .o by mistake?",
+            path.display()
+        ));
+    }
+    if !bytes.starts_with(FEMTO_CONTAINER_MAGIC) {
+        fail(&format!("{} does not start with the Femto-Container magic \"rBPF\".", path.display()));
+    }
+}
+
+fn check_object_program(path: &PathBuf, hint: &str) {
+    let bytes = read_program(path, hint);
+    if !bytes.starts_with(ELF_MAGIC) {
+        fail(&format!("{} is not an ELF object file.", path.display()));
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(1);
 }
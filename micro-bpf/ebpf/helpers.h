@@ -117,4 +117,10 @@ static uint64_t (*bpf_hd44780_set_cursor)(uint32_t dev, uint32_t row,
 static uint64_t (*bpf_keypad_get_input)(uint32_t adc_index) = (void *)
     BPF_KEYPAD_GET_INPUT;
 
+/* VFS calls, negative errno values on failure */
+static int (*bpf_vfs_open)(const char *path) = (void *)BPF_FUNC_BPF_VFS_OPEN;
+static int (*bpf_vfs_read)(int handle, void *buf, uint32_t len) = (void *)
+    BPF_FUNC_BPF_VFS_READ;
+static int (*bpf_vfs_close)(int handle) = (void *)BPF_FUNC_BPF_VFS_CLOSE;
+
 #endif /* BPF_APPLICATION_CALL_H */
@@ -0,0 +1,25 @@
+#include "helpers.h"
+
+#ifndef SCALE_FACTOR
+#define SCALE_FACTOR 1
+#endif
+
+/* Helper mix: time and string helpers interleaved. */
+int benchmark(void)
+{
+    char src[] = "femto-container";
+    char dst[sizeof(src)];
+    uint32_t last_us = 0;
+    for (int i = 0; i < SCALE_FACTOR; i++) {
+        uint32_t now_us = bpf_ztimer_now();
+        if (now_us < last_us) {
+            return 0;
+        }
+        last_us = now_us;
+        bpf_memcpy(dst, src, sizeof(src));
+        if (bpf_strlen(dst) != sizeof(src) - 1) {
+            return 0;
+        }
+    }
+    return 1;
+}
@@ -0,0 +1,19 @@
+#include "helpers.h"
+
+#ifndef SCALE_FACTOR
+#define SCALE_FACTOR 1
+#endif
+
+/* Helper mix: only the memory and string helpers. */
+int benchmark(void)
+{
+    char src[] = "femto-container";
+    char dst[sizeof(src)];
+    for (int i = 0; i < SCALE_FACTOR; i++) {
+        bpf_memcpy(dst, src, sizeof(src));
+        if (bpf_strlen(dst) != sizeof(src) - 1) {
+            return 0;
+        }
+    }
+    return 1;
+}
@@ -0,0 +1,22 @@
+#include "helpers.h"
+
+#ifndef SCALE_FACTOR
+#define SCALE_FACTOR 1
+#endif
+
+/* Helper mix: only the time helpers. */
+int benchmark(void)
+{
+    uint32_t last_us = 0;
+    uint32_t last_ms = 0;
+    for (int i = 0; i < SCALE_FACTOR; i++) {
+        uint32_t now_us = bpf_ztimer_now();
+        uint32_t now_ms = bpf_now_ms();
+        if (now_us < last_us || now_ms < last_ms) {
+            return 0;
+        }
+        last_us = now_us;
+        last_ms = now_ms;
+    }
+    return 1;
+}
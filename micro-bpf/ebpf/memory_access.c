@@ -0,0 +1,29 @@
+#include <stdint.h>
+
+#ifndef SCALE_FACTOR
+#define SCALE_FACTOR 1
+#endif
+
+#define WORDS 64
+
+/* The buffer is only reachable through the last of the allowed memory
+   regions, so that every access is checked against the whole region list
+   (see REGION_COUNT). */
+typedef struct {
+    uint64_t data;
+} region_context;
+
+int benchmark(region_context *ctx)
+{
+    volatile uint32_t *data = (volatile uint32_t *)(uintptr_t)ctx->data;
+    uint32_t sum = 0;
+    for (int i = 0; i < SCALE_FACTOR; i++) {
+        for (int j = 0; j < WORDS; j++) {
+            data[j] = j;
+        }
+        for (int j = 0; j < WORDS; j++) {
+            sum += data[j];
+        }
+    }
+    return sum == SCALE_FACTOR * (WORDS * (WORDS - 1) / 2);
+}
@@ -0,0 +1,47 @@
+#include "helpers.h"
+
+/* Boot-time self-test of the helper set, see src/middleware/selftest.rs.
+   Calls every helper with benign arguments and sets bit i of the result if
+   the i-th entry of ALL_HELPERS behaved. Keep both in the same order. */
+int benchmark(void)
+{
+    uint64_t mask = 0;
+
+    /* BPF_PRINT_DEBUG, BPF_PRINTF: returning at all is a success */
+    bpf_print_debug(0);
+    mask |= 1 << 0;
+    print_str("# selftest printf\n");
+    mask |= 1 << 1;
+
+    /* BPF_MEMCPY */
+    char src[] = "selftest";
+    char dst[sizeof(src)] = { 0 };
+    bpf_memcpy(dst, src, sizeof(src));
+    int equal = 1;
+    for (unsigned i = 0; i < sizeof(src); i++) {
+        if (dst[i] != src[i]) {
+            equal = 0;
+        }
+    }
+    if (equal) {
+        mask |= 1 << 2;
+    }
+
+    /* BPF_NOW_MS, BPF_ZTIMER_NOW: less than a millisecond may pass, but the
+       microsecond clock has to move */
+    uint32_t first_ms = bpf_now_ms();
+    uint32_t first_us = bpf_ztimer_now();
+    if (bpf_now_ms() >= first_ms) {
+        mask |= 1 << 3;
+    }
+    if (bpf_ztimer_now() != first_us) {
+        mask |= 1 << 4;
+    }
+
+    /* BPF_STRLEN */
+    if (bpf_strlen(src) == sizeof(src) - 1) {
+        mask |= 1 << 5;
+    }
+
+    return mask;
+}
@@ -71,6 +71,11 @@ enum {
 
   BPF_KEYPAD_GET_INPUT = 0x84,
 
+  /* VFS, only registered with the vfs-helpers feature */
+  BPF_FUNC_BPF_VFS_OPEN = 0x90,
+  BPF_FUNC_BPF_VFS_READ = 0x91,
+  BPF_FUNC_BPF_VFS_CLOSE = 0x92,
+
 };
 
 /* Helper structs */
@@ -0,0 +1,43 @@
+#include "helpers.h"
+
+#ifndef SCALE_FACTOR
+#define SCALE_FACTOR 1
+#endif
+
+/* Checksum of vfs/test.txt, passed in by the Makefile */
+#ifndef VFS_CHECKSUM
+#define VFS_CHECKSUM 0
+#endif
+
+#define CHUNK 64
+
+/* Reads the test file of the vfs-helpers feature in chunks and checks its
+   FNV-1a checksum. The chunks are read into the buffer of the last allowed
+   memory region, the helpers reject any other destination. */
+typedef struct {
+    uint64_t data;
+} vfs_context;
+
+int benchmark(vfs_context *ctx)
+{
+    uint8_t *buf = (uint8_t *)(uintptr_t)ctx->data;
+    const char *path = "/const/test.txt";
+    for (int i = 0; i < SCALE_FACTOR; i++) {
+        int handle = bpf_vfs_open(path);
+        if (handle < 0) {
+            return 0;
+        }
+        uint32_t hash = 0x811c9dc5;
+        int len;
+        while ((len = bpf_vfs_read(handle, buf, CHUNK)) > 0) {
+            for (int j = 0; j < len; j++) {
+                hash = (hash ^ buf[j]) * 0x01000193;
+            }
+        }
+        bpf_vfs_close(handle);
+        if (len < 0 || hash != VFS_CHECKSUM) {
+            return 0;
+        }
+    }
+    return 1;
+}
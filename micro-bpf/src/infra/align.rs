@@ -0,0 +1,51 @@
+//! Copies the program into a heap buffer aligned to `PROG_ALIGN` bytes,
+//! shifted by `PROG_OFFSET`, before the interpreter loads it. The embedded
+//! program has whatever alignment the linker chose, this makes the alignment
+//! of the interpreter's instruction fetches a parameter of the run.
+
+use alloc::alloc::{alloc, Layout};
+use alloc::format;
+
+use super::config;
+use crate::output::ResultSink;
+
+/// Alignments above this are reported as this.
+const MAX_REPORTED: usize = 4096;
+
+/// Largest power of two the address is a multiple of.
+fn alignment_of(address: usize) -> usize {
+    if address == 0 {
+        return MAX_REPORTED;
+    }
+    (1 << address.trailing_zeros()).min(MAX_REPORTED)
+}
+
+/// Returns the copy of `prog` and reports the alignment of both. The copy is
+/// leaked, like the state of the concurrent mode it has to outlive the
+/// threads running the program.
+pub fn aligned_copy(sink: &mut dyn ResultSink, prog: &'static [u8]) -> &'static [u8] {
+    let align = config::prog_align();
+    let offset = config::prog_offset();
+    let layout = Layout::from_size_align((offset + prog.len()).max(1), align)
+        .unwrap_or_else(|_| panic!("PROG_ALIGN={} is not a power of two", align));
+    let copy = unsafe {
+        let buffer = alloc(layout);
+        if buffer.is_null() {
+            panic!("no heap left for the aligned copy of the program ({} bytes)", layout.size());
+        }
+        let start = buffer.add(offset);
+        core::ptr::copy_nonoverlapping(prog.as_ptr(), start, prog.len());
+        core::slice::from_raw_parts(start, prog.len())
+    };
+    sink.metadata(
+        "prog_align",
+        &format!(
+            "embedded={} copy={} requested={}+{}",
+            alignment_of(prog.as_ptr() as usize),
+            alignment_of(copy.as_ptr() as usize),
+            align,
+            offset
+        ),
+    );
+    copy
+}
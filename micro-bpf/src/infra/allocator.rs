@@ -1,3 +1,9 @@
+use alloc::alloc::{alloc, alloc_zeroed, dealloc, Layout};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
 pub mod allocator {
     use alloc::alloc::*;
     use core::ffi::c_void;
@@ -24,3 +30,87 @@ pub mod allocator {
     #[global_allocator]
     static GLOBAL_ALLOCATOR: Allocator = Allocator;
 }
+
+/// Heap taken by loading a program into a VM: the VM, its helpers and the
+/// allocations done while loading. An estimate, rbpf doesn't allocate
+/// fallibly.
+pub const VM_HEAP: usize = 2 * 1024;
+
+/// An allocation that didn't fit into the heap.
+#[derive(Copy, Clone, Debug)]
+pub struct OutOfMemory {
+    pub size: usize,
+}
+
+/// Checks that `size` bytes can currently be allocated by allocating them
+/// once. Used before allocations which would otherwise abort.
+pub fn probe(size: usize) -> Result<(), OutOfMemory> {
+    let layout = Layout::from_size_align(size.max(1), 8).map_err(|_| OutOfMemory { size })?;
+    unsafe {
+        let ptr = alloc(layout);
+        if ptr.is_null() {
+            return Err(OutOfMemory { size });
+        }
+        dealloc(ptr, layout);
+    }
+    Ok(())
+}
+
+/// Upper bound of the search for the largest block.
+const MAX_PROBE: usize = 1 << 20;
+
+/// Largest block that can currently be allocated, found by bisection.
+pub fn largest_block() -> usize {
+    let mut fits = 0;
+    let mut fails = MAX_PROBE + 1;
+    while fails - fits > 1 {
+        let size = fits + (fails - fits) / 2;
+        if probe(size).is_ok() {
+            fits = size;
+        } else {
+            fails = size;
+        }
+    }
+    fits
+}
+
+fn outcome(size: usize) -> &'static str {
+    if probe(size).is_ok() {
+        "ok"
+    } else {
+        "failed"
+    }
+}
+
+/// Whether the allocations of a load currently fit, e.g. `vm=ok
+/// jit_buffer=failed`. `jit_buffer` is the size of the JIT's buffer, if
+/// there is one.
+pub fn load_allocations(jit_buffer: Option<usize>) -> String {
+    let mut allocations = format!("vm={}", outcome(VM_HEAP));
+    if let Some(size) = jit_buffer {
+        allocations.push_str(&format!(" jit_buffer={}", outcome(size)));
+    }
+    allocations
+}
+
+/// Allocates a zeroed `T` directly on the heap, without building it on the
+/// stack first like `Box::new` does.
+///
+/// # Safety
+///
+/// All zero bytes must be a valid `T`.
+pub unsafe fn try_box_zeroed<T>() -> Result<Box<T>, OutOfMemory> {
+    let layout = Layout::new::<T>();
+    let ptr = alloc_zeroed(layout) as *mut T;
+    if ptr.is_null() {
+        return Err(OutOfMemory { size: layout.size() });
+    }
+    Ok(Box::from_raw(ptr))
+}
+
+pub fn try_to_vec(data: &[u8]) -> Result<Vec<u8>, OutOfMemory> {
+    let mut vec = Vec::new();
+    vec.try_reserve_exact(data.len()).map_err(|_| OutOfMemory { size: data.len() })?;
+    vec.extend_from_slice(data);
+    Ok(vec)
+}
@@ -0,0 +1,63 @@
+//! Receives the program under benchmark over stdio, as a single line of
+//! base64 text, instead of embedding it at build time.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::output::ResultSink;
+use crate::util::base64::{self, Base64Error};
+
+/// Longest line accepted, enough for a 48 KiB program.
+const MAX_LINE: usize = 64 * 1024;
+
+/// Reads a line without the line ending. Returns `None` if it is longer
+/// than `MAX_LINE`, the rest of the line is discarded then.
+fn read_line() -> Option<Vec<u8>> {
+    let mut line = Vec::new();
+    let mut too_long = false;
+    loop {
+        let mut byte = 0u8;
+        let read = unsafe { riot_sys::stdio_read(&mut byte as *mut u8 as *mut _, 1) };
+        if read != 1 {
+            continue;
+        }
+        match byte {
+            b'\n' => break,
+            b'\r' => {}
+            _ if line.len() == MAX_LINE => too_long = true,
+            _ => line.push(byte),
+        }
+    }
+    if too_long {
+        None
+    } else {
+        Some(line)
+    }
+}
+
+/// Prompts for the program until a line decodes. The program stays
+/// allocated until the firmware ends.
+pub fn receive(sink: &mut dyn ResultSink) -> &'static [u8] {
+    loop {
+        sink.comment("load=waiting, send the program as one line of base64");
+        let line = match read_line() {
+            Some(line) => line,
+            None => {
+                sink.comment(&format!("error=line_too_long, the limit is {} characters, send it again", MAX_LINE));
+                continue;
+            }
+        };
+        match base64::decode(&line) {
+            Ok(program) if !program.is_empty() => {
+                sink.metadata("program_bytes", &format!("{}", program.len()));
+                return program.leak();
+            }
+            Ok(_) => sink.comment("error=empty_program, send it again"),
+            Err(Base64Error::InvalidCharacter { position, character }) => sink.comment(&format!(
+                "error=invalid_base64 character={:#04x} position={}, send it again",
+                character, position
+            )),
+            Err(Base64Error::InvalidPadding) => sink.comment("error=invalid_base64_padding, send it again"),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Mean timings of an earlier run, embedded at build time from the file given
+//! in `BASELINE` (see build.rs), to report regressions against.
+
+/// Mean timings of one program in the baseline.
+pub struct BaselineEntry {
+    pub program: &'static str,
+    pub load_program_us: u32,
+    pub execution_time_us: u32,
+}
+
+include!(concat!(env!("OUT_DIR"), "/baseline.rs"));
+
+pub fn lookup(program: &str) -> Option<&'static BaselineEntry> {
+    BASELINE.iter().find(|entry| entry.program == program)
+}
+
+/// Signed change from `baseline` to `current` in hundredths of a percent,
+/// `None` if the baseline is zero.
+pub fn change_centi_pct(current: u32, baseline: u32) -> Option<i64> {
+    if baseline == 0 {
+        return None;
+    }
+    Some((current as i64 - baseline as i64) * 10_000 / baseline as i64)
+}
+
+/// Whether a change is an increase beyond `threshold_pct` percent. Only
+/// increases count, the timings are better the lower they are.
+pub fn is_regression(change_centi_pct: i64, threshold_pct: u32) -> bool {
+    change_centi_pct > threshold_pct as i64 * 100
+}
+
+/// Formats a change in hundredths of a percent as e.g. `+12.34%`.
+pub struct Percent(pub i64);
+
+impl core::fmt::Display for Percent {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let sign = if self.0 < 0 { '-' } else { '+' };
+        let abs = self.0.unsigned_abs();
+        write!(f, "{}{}.{:02}%", sign, abs / 100, abs % 100)
+    }
+}
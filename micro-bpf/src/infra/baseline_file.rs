@@ -0,0 +1,146 @@
+//! The baseline file given in `BASELINE`, parsed by build.rs into the table
+//! of infra::baseline. The crate only builds it for its host tests, build.rs
+//! includes it with `#[path]`.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Parses a captured benchmark output (or the CSV written by
+/// scripts/benchmark.py) into the mean load and execution time per program.
+/// Banner, metadata and log lines are skipped, columns are matched by name.
+pub fn parse_baseline(text: &str) -> Result<Vec<(String, u64, u64)>, String> {
+    let mut header: Option<(char, Vec<String>)> = None;
+    // Programs without a program column are named by the preceding
+    // `# program=` line, or "benchmark" outside of suite mode.
+    let mut section_program = String::from("benchmark");
+    // program, count, load sum, execution sum; in order of appearance
+    let mut sums: Vec<(String, u64, u64, u64)> = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(metadata) = line.strip_prefix('#') {
+            if let Some(program) = metadata.trim().strip_prefix("program=") {
+                section_program = program.trim().to_string();
+            }
+            continue;
+        }
+        if line.starts_with("iteration") || (header.is_none() && line.contains("execution_time_us")) {
+            let delimiter = if line.contains(';') { ';' } else { ',' };
+            header = Some((delimiter, line.split(delimiter).map(|c| c.trim().to_string()).collect()));
+            continue;
+        }
+        let (delimiter, columns) = match &header {
+            Some(header) => header,
+            None => continue,
+        };
+        let fields: Vec<&str> = line.split(*delimiter).map(str::trim).collect();
+        if fields.len() < 2 {
+            // Log output between the rows
+            continue;
+        }
+        if fields.len() != columns.len() {
+            return Err(format!("line {}: expected {} fields, found {}", n + 1, columns.len(), fields.len()));
+        }
+        let field = |name: &str| columns.iter().position(|c| c == name).map(|i| fields[i]);
+        let number = |name: &str| -> Result<u64, String> {
+            let value = field(name).ok_or_else(|| format!("no {} column", name))?;
+            value.parse().map_err(|_| format!("line {}: invalid {} value {:?}", n + 1, name, value))
+        };
+        // The CSV of scripts/benchmark.py contains all runtimes
+        if field("environment").is_some_and(|env| env != "micro-bpf") {
+            continue;
+        }
+        let program = field("program").or_else(|| field("benchmark")).unwrap_or(&section_program).to_string();
+        let load = number("load_program_us")?;
+        let execution = number("execution_time_us")?;
+        match sums.iter_mut().find(|s| s.0 == program) {
+            Some(sum) => {
+                sum.1 += 1;
+                sum.2 += load;
+                sum.3 += execution;
+            }
+            None => sums.push((program, 1, load, execution)),
+        }
+    }
+
+    if header.is_none() {
+        return Err("no header line with an execution_time_us column".to_string());
+    }
+    Ok(sums.into_iter().map(|(program, count, load, execution)| (program, load / count, execution / count)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn means(text: &str) -> Vec<(String, u64, u64)> {
+        parse_baseline(text).unwrap_or_else(|e| panic!("failed to parse: {}", e))
+    }
+
+    fn entry(program: &str, load: u64, execution: u64) -> (String, u64, u64) {
+        (program.to_string(), load, execution)
+    }
+
+    #[test]
+    fn metadata_and_log_lines_are_skipped() {
+        let text = "\
+=== Benchmark Begins ===
+# rbpf_version=0.2.0
+# program=fib
+iteration;load_program_us;execution_time_us;correct
+0;10;100;true
+JIT compiling...
+1;20;300;true
+# program=sort
+0;5;50;true
+=== Benchmark End ===
+";
+        assert_eq!(means(text), [entry("fib", 15, 200), entry("sort", 5, 50)]);
+    }
+
+    #[test]
+    fn extra_columns_are_matched_by_name() {
+        // The CSV of scripts/benchmark.py, with the other runtimes
+        let text = "\
+environment,benchmark,register_us,execution_time_us,load_program_us,iteration
+micro-bpf,fib,3,100,10,0
+wamr,fib,0,7,9,0
+micro-bpf,fib,3,200,30,1
+";
+        assert_eq!(means(text), [entry("fib", 20, 150)]);
+    }
+
+    #[test]
+    fn row_with_extra_fields_is_rejected() {
+        let text = "iteration;load_program_us;execution_time_us\n0;10;100\n1;10;100;7\n";
+        assert_eq!(parse_baseline(text), Err("line 3: expected 3 fields, found 4".to_string()));
+    }
+
+    #[test]
+    fn missing_header_is_rejected() {
+        let text = "# program=fib\n0;10;100;true\n";
+        assert_eq!(parse_baseline(text), Err("no header line with an execution_time_us column".to_string()));
+    }
+
+    #[test]
+    fn missing_column_is_rejected() {
+        let text = "iteration;execution_time_us\n0;100\n";
+        assert_eq!(parse_baseline(text), Err("no load_program_us column".to_string()));
+    }
+
+    #[test]
+    fn non_numeric_cells_are_rejected() {
+        let text = "iteration;load_program_us;execution_time_us\n0;10;na\n";
+        assert_eq!(
+            parse_baseline(text),
+            Err("line 2: invalid execution_time_us value \"na\"".to_string())
+        );
+    }
+
+    #[test]
+    fn program_without_rows_has_no_baseline() {
+        let text = "# program=fib\niteration;load_program_us;execution_time_us\n0;10;100\n# program=sort\n";
+        assert_eq!(means(text), [entry("fib", 10, 100)]);
+    }
+}
@@ -0,0 +1,64 @@
+//! Stops the program at instruction slot `STOP_AT_INSN` of its text section,
+//! to time a prefix of it, e.g. by sweeping the value. rbpf neither counts
+//! the executed instructions nor single steps, the slot is replaced by `exit`
+//! instead: the program returns the first time it reaches the slot, with
+//! whatever is in r0 then. Without loops this is after the first
+//! `STOP_AT_INSN` slots, a jump over the slot runs past it.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use super::config;
+use crate::middleware::analysis::header_text;
+use crate::output::ResultSink;
+
+const INSN_SIZE: usize = 8;
+const LDDW_OPCODE: u8 = 0x18;
+const EXIT: [u8; INSN_SIZE] = [0x95, 0, 0, 0, 0, 0, 0, 0];
+
+/// Whether `slot` starts an instruction, i.e. isn't the second half of a
+/// load double word.
+fn starts_instruction(text: &[u8], slot: usize) -> bool {
+    let mut current = 0;
+    while current < slot {
+        current += if text[current * INSN_SIZE] == LDDW_OPCODE { 2 } else { 1 };
+    }
+    current == slot
+}
+
+/// Returns a copy of `prog` with the breakpoint, leaked like the aligned
+/// copy of align-prog. `None` if the slot isn't an instruction of the
+/// program, this is reported.
+pub fn patch(sink: &mut dyn ResultSink, prog: &'static [u8]) -> Option<&'static [u8]> {
+    let slot = config::stop_at_insn();
+    let text = match header_text(prog) {
+        Some(text) => text,
+        None => {
+            sink.comment("stop_at_insn=refused the program has no valid header");
+            return None;
+        }
+    };
+    let slots = text.len() / INSN_SIZE;
+    if slot >= slots {
+        sink.comment(&format!(
+            "stop_at_insn=refused STOP_AT_INSN={} but the program has {} instruction slots",
+            slot, slots
+        ));
+        return None;
+    }
+    if !starts_instruction(text, slot) {
+        sink.comment(&format!(
+            "stop_at_insn=refused slot {} is the second half of a lddw, use {} or {}",
+            slot,
+            slot - 1,
+            slot + 1
+        ));
+        return None;
+    }
+
+    let start = text.as_ptr() as usize - prog.as_ptr() as usize + slot * INSN_SIZE;
+    let mut patched = Vec::from(prog);
+    patched[start..start + INSN_SIZE].copy_from_slice(&EXIT);
+    sink.metadata("stop_at_insn", &format!("slot={} of={}", slot, slots));
+    Some(patched.leak())
+}
@@ -0,0 +1,137 @@
+//! Runs the same program on several VMs at once, each in its own thread with
+//! equal priority, to measure how the throughput scales.
+//!
+//! The workers only buffer their results, the rows are printed by the caller
+//! once all workers are done so that the output doesn't interleave. The
+//! state the helpers share between the workers, e.g. the counters of
+//! `middleware::profile` and `middleware::vfs`, is held in atomics.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use portable_atomic::{AtomicU32, Ordering};
+use riot_wrappers::thread::KernelPID;
+
+use crate::infra::allocator::{self, VM_HEAP};
+use crate::infra::sync::Barrier;
+use crate::util::time::now_us;
+
+/// Number of threads this mode starts at most.
+pub const MAX_VMS: usize = 4;
+
+/// Results of all workers.
+pub struct Run<T> {
+    /// The rows of each worker, in the order they were spawned.
+    pub rows: Vec<Vec<T>>,
+    /// Time from the common start until the last worker was done.
+    pub wall_us: u32,
+    /// Time from the common start until each worker was done, in the order
+    /// of `rows`.
+    pub worker_wall_us: Vec<u32>,
+}
+
+struct Worker<P: 'static, T: 'static> {
+    prog: &'static P,
+    iterations: usize,
+    iteration: fn(&'static P) -> T,
+    rows: Vec<T>,
+    done_us: u32,
+    barrier: &'static Barrier,
+    start_us: &'static AtomicU32,
+    end_us: &'static AtomicU32,
+}
+
+/// Checks up front that the stacks and buffers of `vms` workers fit into the
+/// heap by allocating them once. Returns the required size if they don't.
+pub fn check_heap<P: 'static, T: 'static>(vms: usize, iterations: usize, stack_size: usize) -> Result<(), usize> {
+    let per_vm = stack_size + iterations * core::mem::size_of::<T>() + core::mem::size_of::<Worker<P, T>>() + VM_HEAP;
+    allocator::probe(vms * per_vm).map_err(|oom| oom.size)
+}
+
+/// Runs `iterations` of `iteration` with `prog` on each of `vms` threads,
+/// which all read the same `prog`. The threads share the priority of the
+/// caller, sched_round_robin slices between them.
+pub fn run<P: Sync + 'static, T: 'static>(
+    vms: usize,
+    iterations: usize,
+    stack_size: usize,
+    prog: &'static P,
+    iteration: fn(&'static P) -> T,
+) -> Run<T> {
+    // The synchronisation state must outlive the workers. It is leaked, this
+    // mode runs once per program.
+    let barrier: &'static Barrier = Box::leak(Box::new(Barrier::new(vms)));
+    let start_us: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+    let end_us: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+
+    let priority = riot_wrappers::thread::get_pid()
+        .priority()
+        .expect("main thread has no priority");
+
+    let mut stacks: Vec<Vec<u8>> = Vec::with_capacity(vms);
+    let mut workers: Vec<*mut Worker<P, T>> = Vec::with_capacity(vms);
+    let mut pids: Vec<KernelPID> = Vec::with_capacity(vms);
+    for _ in 0..vms {
+        let mut stack = alloc::vec![0u8; stack_size];
+        let worker = Box::into_raw(Box::new(Worker {
+            prog,
+            iterations,
+            iteration,
+            rows: Vec::with_capacity(iterations),
+            done_us: 0,
+            barrier,
+            start_us,
+            end_us,
+        }));
+        let pid = unsafe {
+            riot_sys::thread_create(
+                stack.as_mut_ptr() as *mut _,
+                stack_size as _,
+                priority,
+                0,
+                Some(work::<P, T>),
+                worker as *mut _,
+                "vm\0".as_ptr() as *const _,
+            )
+        };
+        pids.push(KernelPID::new(pid).expect("failed to start VM thread"));
+        stacks.push(stack);
+        workers.push(worker);
+    }
+
+    // Wait until the threads are gone, not only done with their work, as
+    // they still use their stack while exiting.
+    while pids.iter().any(|pid| pid.status().is_ok()) {
+        unsafe { riot_sys::ztimer_sleep(riot_sys::ZTIMER_USEC, 1000) };
+    }
+
+    // The workers have exited, their stacks and state are no longer used
+    let start = start_us.load(Ordering::Relaxed);
+    let mut rows = Vec::with_capacity(vms);
+    let mut worker_wall_us = Vec::with_capacity(vms);
+    for worker in workers {
+        let worker = unsafe { Box::from_raw(worker) };
+        worker_wall_us.push(worker.done_us.wrapping_sub(start));
+        rows.push(worker.rows);
+    }
+    drop(stacks);
+
+    Run {
+        rows,
+        wall_us: end_us.load(Ordering::Relaxed).wrapping_sub(start),
+        worker_wall_us,
+    }
+}
+
+unsafe extern "C" fn work<P: 'static, T: 'static>(arg: *mut core::ffi::c_void) -> *mut core::ffi::c_void {
+    let worker = &mut *(arg as *mut Worker<P, T>);
+    if worker.barrier.wait() {
+        worker.start_us.store(now_us(), Ordering::Relaxed);
+    }
+    for _ in 0..worker.iterations {
+        let row = (worker.iteration)(worker.prog);
+        worker.rows.push(row);
+    }
+    worker.done_us = now_us();
+    worker.end_us.fetch_max(worker.done_us, Ordering::Relaxed);
+    core::ptr::null_mut()
+}
@@ -0,0 +1,674 @@
+//! Compile time configuration of the benchmark. The values are forwarded from
+//! the environment of the build by build.rs. The ones the iterations and rows
+//! read are parsed once into a `Config` by main, the others on first use.
+
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use crate::infra::vm::VmKind;
+
+const ITERATIONS: &str = env!("ITERATIONS");
+const MAX_ITERATIONS: &str = env!("MAX_ITERATIONS");
+const WARMUP: &str = env!("WARMUP");
+const WARMUP_WINDOW: &str = env!("WARMUP_WINDOW");
+const WARMUP_SPREAD_PCT: &str = env!("WARMUP_SPREAD_PCT");
+const WARMUP_MAX: &str = env!("WARMUP_MAX");
+const SANITY_MIN_US: &str = env!("SANITY_MIN_US");
+const REGRESSION_THRESHOLD_PCT: &str = env!("REGRESSION_THRESHOLD_PCT");
+const REGRESSION_GATE: &str = env!("REGRESSION_GATE");
+const PERIOD_MS: &str = env!("PERIOD_MS");
+const CONCURRENT_VMS: &str = env!("CONCURRENT_VMS");
+const REGION_COUNT: &str = env!("REGION_COUNT");
+const ROBUST: &str = env!("ROBUST");
+const RESTART_ON_EARLY_FAILURE: &str = env!("RESTART_ON_EARLY_FAILURE");
+const MAX_RESTARTS: &str = env!("MAX_RESTARTS");
+const HELPER_PATTERN: &str = env!("HELPER_PATTERN");
+const EXEC_BATCH: &str = env!("EXEC_BATCH");
+const VM_KIND: &str = env!("VM_KIND");
+const TRIGGER_PERIOD_MS: &str = env!("TRIGGER_PERIOD_MS");
+const MEMBW_EVERY: &str = env!("MEMBW_EVERY");
+const REDUCE: &str = env!("REDUCE");
+const RUN_LABEL: &str = env!("RUN_LABEL");
+const SUCCESS_MODE: &str = env!("SUCCESS_MODE");
+const ARG_ENDIAN: &str = env!("ARG_ENDIAN");
+const DRIFT_THRESHOLD_PCT: &str = env!("DRIFT_THRESHOLD_PCT");
+const DRIFT_WINDOW: &str = env!("DRIFT_WINDOW");
+#[cfg(not(feature = "reference"))]
+const EXPECTED: &str = env!("EXPECTED");
+const LABEL_COLUMN: &str = env!("LABEL_COLUMN");
+#[cfg(feature = "pmu")]
+const PMU_PRESENT: &str = env!("PMU_PRESENT");
+#[cfg(feature = "timer-selftest")]
+const TIMER_SELFTEST_US: &str = env!("TIMER_SELFTEST_US");
+#[cfg(feature = "timer-selftest")]
+const TIMER_SELFTEST_TOLERANCE_PCT: &str = env!("TIMER_SELFTEST_TOLERANCE_PCT");
+#[cfg(feature = "helper-bench")]
+const HELPER_BENCH_ID: &str = env!("HELPER_BENCH_ID");
+#[cfg(feature = "helper-bench")]
+const HELPER_BENCH_CALLS: &str = env!("HELPER_BENCH_CALLS");
+#[cfg(feature = "no-dcache")]
+const DCACHE_PRESENT: &str = env!("DCACHE_PRESENT");
+#[cfg(feature = "seeds")]
+const SEEDS: &str = env!("SEEDS");
+#[cfg(feature = "suite")]
+const BASELINE_PROGRAM: &str = env!("BASELINE_PROGRAM");
+#[cfg(feature = "jit-timeout")]
+const JIT_COMPILE_TIMEOUT_MS: &str = env!("JIT_COMPILE_TIMEOUT_MS");
+#[cfg(feature = "confidence")]
+const CONFIDENCE_PCT: &str = env!("CONFIDENCE_PCT");
+#[cfg(feature = "top-slowest")]
+const TOP_SLOWEST: &str = env!("TOP_SLOWEST");
+#[cfg(feature = "prewarm-mbuff")]
+const MBUFF_PREWARM: &str = env!("MBUFF_PREWARM");
+#[cfg(feature = "reserve-heap")]
+const RESERVE_HEAP_BYTES: &str = env!("RESERVE_HEAP_BYTES");
+#[cfg(feature = "load-throughput")]
+const LOAD_THROUGHPUT_COUNT: &str = env!("LOAD_THROUGHPUT_COUNT");
+#[cfg(feature = "stop-at-insn")]
+const STOP_AT_INSN: &str = env!("STOP_AT_INSN");
+#[cfg(feature = "coap-report")]
+const COAP_REPORT_URI: &str = env!("COAP_REPORT_URI");
+#[cfg(feature = "burst")]
+const BURST_SIZE: &str = env!("BURST_SIZE");
+#[cfg(feature = "stream")]
+const STREAM_TIMEOUT_MS: &str = env!("STREAM_TIMEOUT_MS");
+#[cfg(feature = "align-prog")]
+const PROG_ALIGN: &str = env!("PROG_ALIGN");
+#[cfg(feature = "align-prog")]
+const PROG_OFFSET: &str = env!("PROG_OFFSET");
+#[cfg(feature = "dump-context")]
+const DUMP_CONTEXT: &str = env!("DUMP_CONTEXT");
+#[cfg(feature = "reset-peripherals")]
+const RESET_PERIPHERALS: &str = env!("RESET_PERIPHERALS");
+#[cfg(feature = "fragment-heap")]
+const FRAGMENT_HEAP_KB: &str = env!("FRAGMENT_HEAP_KB");
+#[cfg(feature = "dual-uart")]
+const DATA_UART: &str = env!("DATA_UART");
+#[cfg(feature = "dual-uart")]
+const DATA_UART_BAUD: &str = env!("DATA_UART_BAUD");
+
+/// The configuration read in every program, iteration or row, parsed once at
+/// startup and passed down from main.
+pub struct Config {
+    pub vm_kinds: Vec<VmKind>,
+    pub iterations: usize,
+    pub max_iterations: usize,
+    pub warmup: Warmup,
+    #[cfg(feature = "sanity")]
+    pub sanity_min_us: u32,
+    pub concurrent_vms: usize,
+    pub region_count: usize,
+    pub robust: bool,
+    pub restart_on_early_failure: usize,
+    pub max_restarts: u32,
+    #[cfg(feature = "exec-batch")]
+    pub exec_batch: u32,
+    pub reduce: Reduce,
+    pub success_mode: SuccessMode,
+    pub arg_endian: ArgEndian,
+    pub run_label: &'static str,
+    pub label_column: bool,
+    #[cfg(feature = "membw")]
+    pub membw_every: usize,
+    #[cfg(feature = "helper-pattern")]
+    pub helper_pattern: &'static [u8],
+    #[cfg(feature = "seeds")]
+    pub seeds: Vec<u64>,
+    #[cfg(feature = "confidence")]
+    pub confidence_pct: u32,
+    #[cfg(feature = "stop-at-insn")]
+    pub stop_at_insn: usize,
+    #[cfg(feature = "prewarm-mbuff")]
+    pub mbuff_prewarm_alternate: bool,
+    #[cfg(feature = "reset-peripherals")]
+    pub reset_peripherals: Vec<crate::infra::peripherals::Peripheral>,
+}
+
+impl Config {
+    /// Parses every value, panicking on the first invalid one. With the
+    /// reference feature the reference result has to be computed first.
+    pub fn parse() -> Self {
+        let config = Config {
+            vm_kinds: vm_kinds(),
+            iterations: iterations(),
+            max_iterations: max_iterations(),
+            warmup: warmup(),
+            #[cfg(feature = "sanity")]
+            sanity_min_us: sanity_min_us(),
+            concurrent_vms: concurrent_vms(),
+            region_count: region_count(),
+            robust: robust(),
+            restart_on_early_failure: restart_on_early_failure(),
+            max_restarts: max_restarts(),
+            #[cfg(feature = "exec-batch")]
+            exec_batch: exec_batch(),
+            reduce: reduce(),
+            success_mode: success_mode(),
+            arg_endian: arg_endian(),
+            run_label: run_label(),
+            label_column: label_column(),
+            #[cfg(feature = "membw")]
+            membw_every: membw_every(),
+            #[cfg(feature = "helper-pattern")]
+            helper_pattern: helper_pattern(),
+            #[cfg(feature = "seeds")]
+            seeds: seeds(),
+            #[cfg(feature = "confidence")]
+            confidence_pct: confidence_pct(),
+            #[cfg(feature = "stop-at-insn")]
+            stop_at_insn: stop_at_insn(),
+            #[cfg(feature = "prewarm-mbuff")]
+            mbuff_prewarm_alternate: mbuff_prewarm_alternate(),
+            #[cfg(feature = "reset-peripherals")]
+            reset_peripherals: reset_peripherals(),
+        };
+        // The nodata VM passes the program neither the Context nor the
+        // address of the region buffer, see `Vm::execute_program`.
+        let needs_memory = cfg!(feature = "libud") || cfg!(feature = "vfs-helpers") || config.region_count > 0;
+        if needs_memory && config.vm_kinds.contains(&VmKind::NoData) {
+            panic!("VM_KIND=nodata passes the program no memory, it can't run with BENCHMARK=libud, REGION_COUNT>0 or VFS_HELPERS=1");
+        }
+        if config.concurrent_vms > 1 && !cfg!(feature = "multithread") {
+            panic!("CONCURRENT_VMS={} needs the multithread feature, set THREAD_COUNT instead", config.concurrent_vms);
+        }
+        config
+    }
+
+    /// Recorded iterations a program is run for: `ITERATIONS`, or up to
+    /// `MAX_ITERATIONS` with `CONFIDENCE_PCT`, which stops on its own.
+    /// `MAX_ITERATIONS` bounds either.
+    pub fn planned_iterations(&self) -> usize {
+        if cfg!(feature = "confidence") {
+            self.max_iterations
+        } else {
+            self.iterations.min(self.max_iterations)
+        }
+    }
+}
+
+/// Iterations run before the recorded ones, selected with `WARMUP`.
+#[derive(Copy, Clone)]
+pub enum Warmup {
+    /// `WARMUP=0`: every iteration is recorded.
+    None,
+    /// `WARMUP=<n>`: a fixed number of iterations is discarded.
+    Fixed(usize),
+    /// `WARMUP=auto`: iterations are discarded until the relative spread of
+    /// the last `window` execution times is at most `spread_pct` percent,
+    /// but at most `max` iterations.
+    Auto {
+        window: usize,
+        spread_pct: u32,
+        max: usize,
+    },
+}
+
+/// How the results of the executions within one measurement (see
+/// `EXEC_BATCH`) are reduced into its `correct` column, selected with
+/// `REDUCE`.
+#[derive(Copy, Clone)]
+pub enum Reduce {
+    /// Every execution returned the expected result.
+    All,
+    /// The first execution did, the others are ignored.
+    First,
+    /// More than half of the executions did.
+    Majority,
+}
+
+impl Reduce {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Reduce::All => "all",
+            Reduce::First => "first",
+            Reduce::Majority => "majority",
+        }
+    }
+
+    /// `passed` of the `calls` executions were correct, `first` tells
+    /// whether the first one was.
+    pub fn reduce(&self, first: bool, passed: u32, calls: u32) -> bool {
+        match self {
+            Reduce::All => passed == calls,
+            Reduce::First => first,
+            Reduce::Majority => passed * 2 > calls,
+        }
+    }
+}
+
+/// When an execution counts as correct, selected with `SUCCESS_MODE`.
+#[derive(Copy, Clone)]
+pub enum SuccessMode {
+    /// `SUCCESS_MODE=expected`: the program returned `EXPECTED`. A failed
+    /// execution is incorrect.
+    Expected(u64),
+    /// `SUCCESS_MODE=completed`: the execution didn't fail, whatever the
+    /// program returned. For programs which only have side effects.
+    Completed,
+}
+
+impl SuccessMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SuccessMode::Expected(_) => "expected",
+            SuccessMode::Completed => "completed",
+        }
+    }
+
+    /// A failed execution is never a success, it is left to ROBUST and REDUCE
+    /// like a wrong result.
+    pub fn is_success<E>(&self, outcome: Result<u64, E>) -> bool {
+        match self {
+            SuccessMode::Expected(expected) => outcome.is_ok_and(|result| result == *expected),
+            SuccessMode::Completed => outcome.is_ok(),
+        }
+    }
+}
+
+/// Byte order of the multi-byte values written into the mbuff, selected with
+/// `ARG_ENDIAN`, for programs compiled with a different assumption than the
+/// board's.
+#[derive(Copy, Clone)]
+pub enum ArgEndian {
+    Native,
+    Little,
+    Big,
+}
+
+impl ArgEndian {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArgEndian::Native if cfg!(target_endian = "big") => "native (big)",
+            ArgEndian::Native => "native (little)",
+            ArgEndian::Little => "little",
+            ArgEndian::Big => "big",
+        }
+    }
+
+    pub fn encode_u64(&self, value: u64) -> [u8; 8] {
+        match self {
+            ArgEndian::Native => value.to_ne_bytes(),
+            ArgEndian::Little => value.to_le_bytes(),
+            ArgEndian::Big => value.to_be_bytes(),
+        }
+    }
+}
+
+pub fn iterations() -> usize {
+    // One iteration per seed, whatever ITERATIONS says
+    #[cfg(feature = "seeds")]
+    return seeds().len();
+    #[cfg(not(feature = "seeds"))]
+    parse("ITERATIONS", ITERATIONS)
+}
+
+/// Hard limit of the iterations run per program, discarded ones included.
+pub fn max_iterations() -> usize {
+    parse("MAX_ITERATIONS", MAX_ITERATIONS)
+}
+
+pub fn warmup() -> Warmup {
+    match WARMUP {
+        "auto" => Warmup::Auto {
+            window: parse("WARMUP_WINDOW", WARMUP_WINDOW),
+            spread_pct: parse("WARMUP_SPREAD_PCT", WARMUP_SPREAD_PCT),
+            max: parse("WARMUP_MAX", WARMUP_MAX),
+        },
+        count => match parse("WARMUP", count) {
+            0 => Warmup::None,
+            count => Warmup::Fixed(count),
+        },
+    }
+}
+
+/// Execution time below which the sanity check considers a program a no-op.
+pub fn sanity_min_us() -> u32 {
+    parse("SANITY_MIN_US", SANITY_MIN_US)
+}
+
+/// Increase over the baseline mean, in percent, above which a metric is
+/// flagged as a regression.
+pub fn regression_threshold_pct() -> u32 {
+    parse("REGRESSION_THRESHOLD_PCT", REGRESSION_THRESHOLD_PCT)
+}
+
+/// Whether regressions against the baseline fail the verdict
+/// (`REGRESSION_GATE=1`) or are only reported.
+pub fn regression_gate() -> bool {
+    parse::<u8>("REGRESSION_GATE", REGRESSION_GATE) == 1
+}
+
+/// Increase over the mean of the first `DRIFT_WINDOW` iterations, in percent,
+/// above which an iteration raises a drift warning. 0 disables the warnings.
+pub fn drift_threshold_pct() -> u32 {
+    parse("DRIFT_THRESHOLD_PCT", DRIFT_THRESHOLD_PCT)
+}
+
+/// Iterations whose mean execution time is the drift baseline.
+pub fn drift_window() -> u32 {
+    parse("DRIFT_WINDOW", DRIFT_WINDOW)
+}
+
+/// Wake-up period of the interference thread.
+pub fn period_ms() -> u32 {
+    parse("PERIOD_MS", PERIOD_MS)
+}
+
+/// Number of VMs executing the program at the same time, 1 runs the
+/// iterations on the main thread.
+pub fn concurrent_vms() -> usize {
+    parse("CONCURRENT_VMS", CONCURRENT_VMS)
+}
+
+/// Length of the allowed memory region list passed to the VM, 0 passes an
+/// empty list.
+pub fn region_count() -> usize {
+    parse("REGION_COUNT", REGION_COUNT)
+}
+
+/// Whether iterations which fail are skipped (`ROBUST=1`) instead of
+/// stopping the run.
+pub fn robust() -> bool {
+    parse::<u8>("ROBUST", ROBUST) == 1
+}
+
+/// Number of first iterations in which a failure restarts the recorded
+/// iterations, 0 if it never does.
+pub fn restart_on_early_failure() -> usize {
+    parse("RESTART_ON_EARLY_FAILURE", RESTART_ON_EARLY_FAILURE)
+}
+
+pub fn max_restarts() -> u32 {
+    parse("MAX_RESTARTS", MAX_RESTARTS)
+}
+
+/// Pattern of the helper-pattern feature, 1 registers the helpers in an
+/// iteration and 0 skips them, repeated over the iterations.
+pub fn helper_pattern() -> &'static [u8] {
+    let pattern = HELPER_PATTERN.as_bytes();
+    if pattern.is_empty() || pattern.iter().any(|c| *c != b'0' && *c != b'1') {
+        panic!("Failed to parse HELPER_PATTERN={}, expected a string of 0 and 1", HELPER_PATTERN);
+    }
+    pattern
+}
+
+/// Executions of the loaded program per measurement with exec-batch.
+pub fn exec_batch() -> u32 {
+    match parse("EXEC_BATCH", EXEC_BATCH) {
+        0 => panic!("EXEC_BATCH must be at least 1"),
+        batch => batch,
+    }
+}
+
+/// The VM kinds to run the benchmark on, one after another.
+pub fn vm_kinds() -> Vec<VmKind> {
+    let kinds: Vec<VmKind> = VM_KIND
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            VmKind::parse(name)
+                .unwrap_or_else(|| panic!("Failed to parse VM_KIND={}, expected mbuff, raw or nodata", VM_KIND))
+        })
+        .collect();
+    if kinds.is_empty() {
+        panic!("VM_KIND lists no VM kind");
+    }
+    kinds
+}
+
+/// Period of the trigger in the event-driven mode, 0 runs the iterations back
+/// to back.
+pub fn trigger_period_ms() -> u32 {
+    parse("TRIGGER_PERIOD_MS", TRIGGER_PERIOD_MS)
+}
+
+pub fn reduce() -> Reduce {
+    match REDUCE {
+        "all" => Reduce::All,
+        "first" => Reduce::First,
+        "majority" => Reduce::Majority,
+        _ => panic!("Failed to parse REDUCE={}, expected all, first or majority", REDUCE),
+    }
+}
+
+pub fn success_mode() -> SuccessMode {
+    match SUCCESS_MODE {
+        "expected" => SuccessMode::Expected(expected()),
+        "completed" => SuccessMode::Completed,
+        _ => panic!("Failed to parse SUCCESS_MODE={}, expected expected or completed", SUCCESS_MODE),
+    }
+}
+
+pub fn arg_endian() -> ArgEndian {
+    match ARG_ENDIAN {
+        "native" => ArgEndian::Native,
+        "little" => ArgEndian::Little,
+        "big" => ArgEndian::Big,
+        _ => panic!("Failed to parse ARG_ENDIAN={}, expected native, little or big", ARG_ENDIAN),
+    }
+}
+
+/// The expected result, computed by the native reference with the reference
+/// feature.
+fn expected() -> u64 {
+    #[cfg(feature = "reference")]
+    return crate::infra::reference::expected();
+    #[cfg(not(feature = "reference"))]
+    parse("EXPECTED", EXPECTED)
+}
+
+/// Opaque label of the run from `RUN_LABEL`, empty if none was given.
+pub fn run_label() -> &'static str {
+    RUN_LABEL
+}
+
+/// Whether every row repeats the run label in a label column.
+pub fn label_column() -> bool {
+    parse::<u8>("LABEL_COLUMN", LABEL_COLUMN) == 1
+}
+
+/// Iterations between two runs of the memory bandwidth probe.
+pub fn membw_every() -> usize {
+    match parse("MEMBW_EVERY", MEMBW_EVERY) {
+        0 => panic!("MEMBW_EVERY must be at least 1"),
+        every => every,
+    }
+}
+
+/// Whether the core has an Armv8.1-M PMU, decided by the Makefile from
+/// `CPU_CORE`.
+#[cfg(feature = "pmu")]
+pub fn pmu_present() -> bool {
+    parse::<u8>("PMU_PRESENT", PMU_PRESENT) == 1
+}
+
+/// Duration of the sleep measured by timer-selftest.
+#[cfg(feature = "timer-selftest")]
+pub fn timer_selftest_us() -> u32 {
+    match parse("TIMER_SELFTEST_US", TIMER_SELFTEST_US) {
+        0 => panic!("TIMER_SELFTEST_US must be at least 1"),
+        us => us,
+    }
+}
+
+/// How much longer than `TIMER_SELFTEST_US` the sleep may take, in percent.
+#[cfg(feature = "timer-selftest")]
+pub fn timer_selftest_tolerance_pct() -> u32 {
+    parse("TIMER_SELFTEST_TOLERANCE_PCT", TIMER_SELFTEST_TOLERANCE_PCT)
+}
+
+/// Id of the helper called directly by helper-bench, hex with 0x or decimal.
+#[cfg(feature = "helper-bench")]
+pub fn helper_bench_id() -> u32 {
+    let parsed = match HELPER_BENCH_ID.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => HELPER_BENCH_ID.parse().ok(),
+    };
+    parsed.unwrap_or_else(|| panic!("Failed to parse HELPER_BENCH_ID={}", HELPER_BENCH_ID))
+}
+
+/// Calls of the helper timed under one timer read.
+#[cfg(feature = "helper-bench")]
+pub fn helper_bench_calls() -> u32 {
+    parse("HELPER_BENCH_CALLS", HELPER_BENCH_CALLS)
+}
+
+/// Whether the core can have a data cache, decided by the Makefile from
+/// `CPU_CORE`.
+#[cfg(feature = "no-dcache")]
+pub fn dcache_present() -> bool {
+    parse::<u8>("DCACHE_PRESENT", DCACHE_PRESENT) == 1
+}
+
+/// The seeds of the inputs from `SEEDS`, in the order they are run.
+#[cfg(feature = "seeds")]
+pub fn seeds() -> Vec<u64> {
+    let seeds: Vec<u64> = SEEDS
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|seed| !seed.is_empty())
+        .map(|seed| parse("SEEDS", seed))
+        .collect();
+    if seeds.is_empty() {
+        panic!("SEEDS lists no seed");
+    }
+    seeds
+}
+
+/// Program of the suite the others are reported relative to, if any.
+#[cfg(feature = "suite")]
+pub fn baseline_program() -> Option<&'static str> {
+    Some(BASELINE_PROGRAM).filter(|name| !name.is_empty())
+}
+
+/// Time the JIT compilation may take before the board is rebooted.
+#[cfg(feature = "jit-timeout")]
+pub fn jit_compile_timeout_ms() -> u32 {
+    parse("JIT_COMPILE_TIMEOUT_MS", JIT_COMPILE_TIMEOUT_MS)
+}
+
+/// Half-width of the 95% confidence interval of the mean execution time, in
+/// percent of the mean, at which the iterations stop.
+#[cfg(feature = "confidence")]
+pub fn confidence_pct() -> u32 {
+    parse("CONFIDENCE_PCT", CONFIDENCE_PCT)
+}
+
+/// Iterations with the highest execution times listed in the summary.
+#[cfg(feature = "top-slowest")]
+pub fn top_slowest() -> usize {
+    parse("TOP_SLOWEST", TOP_SLOWEST)
+}
+
+/// Whether only every other iteration prewarms the mbuff, otherwise every
+/// one does.
+#[cfg(feature = "prewarm-mbuff")]
+pub fn mbuff_prewarm_alternate() -> bool {
+    match MBUFF_PREWARM {
+        "1" => false,
+        "alternate" => true,
+        _ => panic!("Failed to parse MBUFF_PREWARM={}, expected 1 or alternate", MBUFF_PREWARM),
+    }
+}
+
+/// Heap held in one block during the benchmark.
+#[cfg(feature = "reserve-heap")]
+pub fn reserve_heap_bytes() -> usize {
+    parse("RESERVE_HEAP_BYTES", RESERVE_HEAP_BYTES)
+}
+
+/// Programs loaded in total by the load throughput measurement.
+#[cfg(feature = "load-throughput")]
+pub fn load_throughput_count() -> usize {
+    parse("LOAD_THROUGHPUT_COUNT", LOAD_THROUGHPUT_COUNT)
+}
+
+/// Instruction slot of the text section replaced by the breakpoint.
+#[cfg(feature = "stop-at-insn")]
+pub fn stop_at_insn() -> usize {
+    match parse("STOP_AT_INSN", STOP_AT_INSN) {
+        0 => panic!("STOP_AT_INSN must be at least 1"),
+        slot => slot,
+    }
+}
+
+/// Where the JSON record of the run is POSTed to.
+#[cfg(feature = "coap-report")]
+pub fn coap_report_uri() -> &'static str {
+    COAP_REPORT_URI
+}
+
+/// Iterations per burst, reduced to the maximum execution time.
+#[cfg(feature = "burst")]
+pub fn burst_size() -> u32 {
+    match parse("BURST_SIZE", BURST_SIZE) {
+        0 => panic!("BURST_SIZE must be at least 1"),
+        size => size,
+    }
+}
+
+/// Time to wait for the host to acknowledge a row.
+#[cfg(feature = "stream")]
+pub fn stream_timeout_ms() -> u32 {
+    parse("STREAM_TIMEOUT_MS", STREAM_TIMEOUT_MS)
+}
+
+/// Alignment of the buffer the program is copied into.
+#[cfg(feature = "align-prog")]
+pub fn prog_align() -> usize {
+    parse("PROG_ALIGN", PROG_ALIGN)
+}
+
+/// Bytes between the aligned start of the buffer and the program.
+#[cfg(feature = "align-prog")]
+pub fn prog_offset() -> usize {
+    parse("PROG_OFFSET", PROG_OFFSET)
+}
+
+/// Whether the Context of every recorded iteration is dumped, otherwise
+/// only the first one of each program.
+#[cfg(feature = "dump-context")]
+pub fn dump_context_all() -> bool {
+    match DUMP_CONTEXT {
+        "first" => false,
+        "all" => true,
+        _ => panic!("Failed to parse DUMP_CONTEXT={}, expected first or all", DUMP_CONTEXT),
+    }
+}
+
+/// The peripherals reset before each iteration.
+#[cfg(feature = "reset-peripherals")]
+pub fn reset_peripherals() -> Vec<crate::infra::peripherals::Peripheral> {
+    use crate::infra::peripherals::Peripheral;
+    RESET_PERIPHERALS
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            Peripheral::parse(name).unwrap_or_else(|| {
+                panic!("Failed to parse RESET_PERIPHERALS={}, expected gpio:PORT.PIN entries", RESET_PERIPHERALS)
+            })
+        })
+        .collect()
+}
+
+/// Heap in KiB allocated in small blocks to fragment it.
+#[cfg(feature = "fragment-heap")]
+pub fn fragment_heap_kb() -> usize {
+    parse("FRAGMENT_HEAP_KB", FRAGMENT_HEAP_KB)
+}
+
+/// Number of the UART the rows are written to, `UART_DEV(n)`.
+#[cfg(feature = "dual-uart")]
+pub fn data_uart() -> u32 {
+    parse("DATA_UART", DATA_UART)
+}
+
+#[cfg(feature = "dual-uart")]
+pub fn data_uart_baud() -> u32 {
+    parse("DATA_UART_BAUD", DATA_UART_BAUD)
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> T {
+    value
+        .parse()
+        .unwrap_or_else(|_| panic!("Failed to parse {}={}", name, value))
+}
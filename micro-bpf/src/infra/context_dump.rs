@@ -0,0 +1,48 @@
+//! Dumps the libud Context after an execution, to check what the program
+//! computed besides its return value. `mem` is a view of the Context, the
+//! writes of the program are in it.
+//!
+//! With `DUMP_CONTEXT=first` only the first recorded iteration of a program
+//! is dumped, with `all` every one. The Context is copied outside of the
+//! timed regions and printed with the row.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use portable_atomic::{AtomicBool, Ordering};
+
+use super::config;
+use crate::output::ResultSink;
+use crate::Context;
+
+static CAPTURE: AtomicBool = AtomicBool::new(false);
+
+/// Enables the copy of the next Context, before the recorded iterations.
+pub fn arm() {
+    CAPTURE.store(true, Ordering::Relaxed);
+}
+
+/// Copies the Context if a dump is due.
+pub fn capture(ctx: &Context) -> Option<Box<Context>> {
+    if CAPTURE.swap(config::dump_context_all(), Ordering::Relaxed) {
+        Some(Box::new(ctx.clone()))
+    } else {
+        None
+    }
+}
+
+fn join(values: &[i64]) -> String {
+    let values: Vec<String> = values.iter().map(|value| format!("{}", value)).collect();
+    values.join(",")
+}
+
+/// Prints the fields of the Context, one line per row of `a`.
+pub fn report(sink: &mut dyn ResultSink, run: usize, ctx: &Context) {
+    for (i, row) in ctx.a.iter().enumerate() {
+        sink.comment(&format!("context run={} a[{}]={}", run, i, join(row)));
+    }
+    sink.comment(&format!("context run={} b={}", run, join(&ctx.b)));
+    sink.comment(&format!("context run={} x={}", run, join(&ctx.x)));
+    sink.comment(&format!("context run={} y={}", run, join(&ctx.y)));
+}
@@ -0,0 +1,81 @@
+//! Disables the L1 data cache of the core for the whole benchmark, for
+//! worst-case timings that don't depend on the cache. The cache is cleaned
+//! and invalidated first, as CMSIS' SCB_DisableDCache does, and enabled again
+//! when the benchmark ends. Cores without a configurable data cache (see
+//! `DCACHE_PRESENT` in the Makefile) run with their memory system unchanged.
+
+use crate::infra::config;
+
+const CLIDR: usize = 0xE000_ED78;
+const CCSIDR: usize = 0xE000_ED80;
+const CSSELR: usize = 0xE000_ED84;
+const CCR: usize = 0xE000_ED14;
+/// Clean and invalidate by set/way.
+const DCCISW: usize = 0xE000_EF74;
+/// Invalidate by set/way.
+const DCISW: usize = 0xE000_EF60;
+const CCR_DC: u32 = 1 << 16;
+/// Ctype1 of CLIDR, the level 1 cache has a data cache.
+const CLIDR_DCACHE: u32 = 1 << 1;
+const SET_POS: u32 = 5;
+const WAY_POS: u32 = 30;
+
+fn read(address: usize) -> u32 {
+    unsafe { core::ptr::read_volatile(address as *const u32) }
+}
+
+fn write(address: usize, value: u32) {
+    unsafe { core::ptr::write_volatile(address as *mut u32, value) }
+}
+
+fn barrier() {
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("dsb", "isb")
+    };
+}
+
+/// Writes every set/way of the L1 data cache to the maintenance `register`.
+/// The way position is the one of the 4-way caches of the Cortex-M7/M55/M85.
+fn for_each_line(register: usize) {
+    write(CSSELR, 0);
+    barrier();
+    let ccsidr = read(CCSIDR);
+    let sets = (ccsidr >> 13) & 0x7FFF;
+    let ways = (ccsidr >> 3) & 0x3FF;
+    for set in 0..=sets {
+        for way in 0..=ways {
+            write(register, (set << SET_POS) | (way << WAY_POS));
+        }
+    }
+    barrier();
+}
+
+/// Enables the cache again once dropped.
+pub struct Disabled(());
+
+impl Drop for Disabled {
+    fn drop(&mut self) {
+        for_each_line(DCISW);
+        write(CCR, read(CCR) | CCR_DC);
+        barrier();
+    }
+}
+
+/// Whether the core has a data cache which can be disabled.
+pub fn available() -> bool {
+    // CLIDR is only read on cores which have it
+    config::dcache_present() && read(CLIDR) & CLIDR_DCACHE != 0
+}
+
+/// Disables the data cache, `None` if it is off already and stays off.
+pub fn disable() -> Option<Disabled> {
+    if read(CCR) & CCR_DC == 0 {
+        return None;
+    }
+    barrier();
+    write(CCR, read(CCR) & !CCR_DC);
+    barrier();
+    for_each_line(DCCISW);
+    Some(Disabled(()))
+}
@@ -0,0 +1,67 @@
+//! Both encodings of the embedded programs, looked up by the name of the
+//! program: the Femto-Container binary the interpreter loads and the raw
+//! object file the JIT compiles. The table is generated by build.rs, for the
+//! program under benchmark (named `benchmark`) or each program of `SUITE`,
+//! with the encodings that were built.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::middleware::analysis::ProgramFormat;
+use crate::output::ResultSink;
+
+/// The encodings embedded of a single program.
+pub struct Encodings {
+    pub name: &'static str,
+    pub header: Option<&'static [u8]>,
+    pub raw: Option<&'static [u8]>,
+}
+
+include!(concat!(env!("OUT_DIR"), "/encodings.rs"));
+
+fn extension(format: ProgramFormat) -> &'static str {
+    match format {
+        ProgramFormat::FemtoContainersHeader => "bin",
+        ProgramFormat::RawObjectFile => "o",
+    }
+}
+
+/// The program `name` in `format`, if it is embedded.
+pub fn find(name: &str, format: ProgramFormat) -> Option<&'static [u8]> {
+    let program = PROGRAMS.iter().find(|program| program.name == name)?;
+    match format {
+        ProgramFormat::FemtoContainersHeader => program.header,
+        ProgramFormat::RawObjectFile => program.raw,
+    }
+}
+
+/// The program `name` in `format`, for features which can't run without it.
+pub fn require(name: &str, format: ProgramFormat) -> &'static [u8] {
+    find(name, format).unwrap_or_else(|| {
+        if PROGRAMS.iter().any(|program| program.name == name) {
+            panic!("{}.{} is not embedded, build it before the firmware", name, extension(format))
+        }
+        panic!("no program {} is embedded", name)
+    })
+}
+
+/// Reports the encodings embedded of each program.
+pub fn report(sink: &mut dyn ResultSink) {
+    let programs: Vec<String> = PROGRAMS
+        .iter()
+        .map(|program| {
+            let formats = [
+                (ProgramFormat::FemtoContainersHeader, program.header.is_some()),
+                (ProgramFormat::RawObjectFile, program.raw.is_some()),
+            ];
+            let embedded: Vec<&str> = formats
+                .iter()
+                .filter(|(_, embedded)| *embedded)
+                .map(|(format, _)| extension(*format))
+                .collect();
+            format!("{}={}", program.name, embedded.join("+"))
+        })
+        .collect();
+    sink.metadata("encodings", &programs.join(","));
+}
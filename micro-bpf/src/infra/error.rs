@@ -0,0 +1,215 @@
+//! Errors which end an iteration before it produced a measurement.
+
+use alloc::format;
+#[cfg(feature = "error-summary")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "error-summary")]
+use alloc::vec::Vec;
+
+use crate::infra::allocator::OutOfMemory;
+use crate::output::ResultSink;
+
+pub enum BenchError {
+    OutOfMemory(OutOfMemory),
+    /// The program doesn't match its signature, or has none.
+    InvalidSignature,
+    /// The program doesn't have a valid header or doesn't match its manifest.
+    InvalidManifest,
+    /// The program calls the helper with this id, which isn't implemented.
+    UnimplementedHelper(u32),
+    /// An iteration which isn't recorded damaged a canary around the
+    /// program's memory, the damage is reported with the iteration.
+    MemorySafety,
+}
+
+impl BenchError {
+    /// Whether later iterations may succeed. Robust mode skips iterations
+    /// with transient errors, otherwise they stop the whole run. Other errors
+    /// only stop the program they occurred in.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            BenchError::OutOfMemory(_) => true,
+            BenchError::InvalidSignature => false,
+            BenchError::InvalidManifest => false,
+            BenchError::UnimplementedHelper(_) => false,
+            BenchError::MemorySafety => false,
+        }
+    }
+
+    /// Whether the error stops the whole run rather than only its program.
+    pub fn aborts_run(&self) -> bool {
+        self.is_transient() || matches!(self, BenchError::MemorySafety)
+    }
+
+    /// Short name of the error, as in the `error=` comments.
+    pub fn code(&self) -> &'static str {
+        match self {
+            BenchError::OutOfMemory(_) => "out_of_memory",
+            BenchError::InvalidSignature => "invalid_signature",
+            BenchError::InvalidManifest => "invalid_manifest",
+            BenchError::UnimplementedHelper(_) => "unimplemented_helper",
+            BenchError::MemorySafety => "memory_safety",
+        }
+    }
+
+    pub fn report(&self, sink: &mut dyn ResultSink) {
+        match self {
+            BenchError::OutOfMemory(oom) => {
+                sink.banner("OUT OF MEMORY during load");
+                sink.comment(&format!("out_of_memory requested_bytes={}", oom.size));
+            }
+            BenchError::InvalidSignature => {
+                sink.comment("error=invalid_signature, refusing to run the program");
+            }
+            BenchError::InvalidManifest => {
+                sink.comment("error=invalid_manifest, refusing to run the program");
+            }
+            BenchError::UnimplementedHelper(id) => {
+                sink.comment(&format!(
+                    "error=unimplemented_helper helper={:#04x}, refusing to load the program",
+                    id
+                ));
+            }
+            BenchError::MemorySafety => {
+                sink.comment("error=memory_safety, aborting the run");
+            }
+        }
+    }
+}
+
+/// Iterations listed per error in the summary.
+#[cfg(feature = "error-summary")]
+const FIRST_OCCURRENCES: usize = 3;
+
+/// The errors of one program of a recorded iteration.
+#[cfg(feature = "error-summary")]
+struct ErrorCount {
+    program: String,
+    code: &'static str,
+    count: u32,
+    first_runs: Vec<usize>,
+}
+
+/// Errors ending recorded iterations, counted per program and code for the
+/// summary at the end of the run.
+#[cfg(feature = "error-summary")]
+pub struct ErrorTally {
+    entries: Vec<ErrorCount>,
+}
+
+#[cfg(feature = "error-summary")]
+impl ErrorTally {
+    pub fn new() -> Self {
+        ErrorTally { entries: Vec::new() }
+    }
+
+    pub fn add(&mut self, program: &str, run: usize, error: &BenchError) {
+        self.add_code(program, run, error.code());
+    }
+
+    /// Counts a failure which isn't a `BenchError`, e.g. `execution_failed`
+    /// for an execution rbpf returned an error for.
+    pub fn add_code(&mut self, program: &str, run: usize, code: &'static str) {
+        let entry = self.entry(program, code);
+        entry.count += 1;
+        if entry.first_runs.len() < FIRST_OCCURRENCES {
+            entry.first_runs.push(run);
+        }
+    }
+
+    fn entry(&mut self, program: &str, code: &'static str) -> &mut ErrorCount {
+        let index = match self.entries.iter().position(|entry| entry.program == program && entry.code == code) {
+            Some(index) => index,
+            None => {
+                self.entries.push(ErrorCount {
+                    program: program.to_string(),
+                    code,
+                    count: 0,
+                    first_runs: Vec::with_capacity(FIRST_OCCURRENCES),
+                });
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[index]
+    }
+
+    /// Adds the errors of a program to those of the run. The errors of the
+    /// same program and code, e.g. of another VM_KIND, are merged into one
+    /// entry.
+    pub fn merge(&mut self, other: &ErrorTally) {
+        for other in other.entries.iter() {
+            let entry = self.entry(&other.program, other.code);
+            entry.count += other.count;
+            let free = FIRST_OCCURRENCES - entry.first_runs.len();
+            entry.first_runs.extend(other.first_runs.iter().take(free));
+        }
+    }
+
+    /// One line per program and code, nothing if no iteration failed.
+    pub fn report(&self, sink: &mut dyn ResultSink) {
+        if self.entries.is_empty() {
+            return;
+        }
+        sink.banner("Error Summary");
+        for entry in self.entries.iter() {
+            let runs: Vec<String> = entry.first_runs.iter().map(|run| format!("{}", run)).collect();
+            sink.comment(&format!(
+                "error_summary program={} code={} count={} first_runs={}",
+                entry.program,
+                entry.code,
+                entry.count,
+                runs.join(",")
+            ));
+        }
+    }
+}
+
+#[cfg(feature = "error-summary")]
+impl Default for ErrorTally {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<OutOfMemory> for BenchError {
+    fn from(oom: OutOfMemory) -> Self {
+        BenchError::OutOfMemory(oom)
+    }
+}
+
+#[cfg(all(test, feature = "error-summary"))]
+mod tests {
+    use super::*;
+    use crate::output::TextSink;
+
+    fn report(tally: &ErrorTally) -> String {
+        let mut sink = TextSink(String::new());
+        tally.report(&mut sink);
+        sink.0
+    }
+
+    #[test]
+    fn merge_folds_the_same_program_and_code() {
+        let mut run = ErrorTally::new();
+        // The same program on two VM kinds
+        for runs in [[0, 4], [1, 2]].iter() {
+            let mut program = ErrorTally::new();
+            for run in runs.iter() {
+                program.add_code("benchmark", *run, "execution_failed");
+            }
+            program.add("benchmark", 7, &BenchError::InvalidManifest);
+            run.merge(&program);
+        }
+        assert_eq!(
+            report(&run),
+            "=== Error Summary ===\n\
+             # error_summary program=benchmark code=execution_failed count=4 first_runs=0,4,1\n\
+             # error_summary program=benchmark code=invalid_manifest count=2 first_runs=7,7\n"
+        );
+    }
+
+    #[test]
+    fn nothing_is_reported_without_errors() {
+        assert_eq!(report(&ErrorTally::new()), "");
+    }
+}
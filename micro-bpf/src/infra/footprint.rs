@@ -0,0 +1,46 @@
+//! Size of the firmware's sections, to relate the timings to the footprint
+//! of the build (e.g. JIT vs interpreter). The bounds come from the symbols
+//! of RIOT's Cortex-M linker script, other architectures report none.
+
+#[cfg(target_arch = "arm")]
+use alloc::format;
+
+use crate::output::ResultSink;
+
+#[cfg(target_arch = "arm")]
+extern "C" {
+    static _sfixed: u8;
+    static _efixed: u8;
+    static _srelocate: u8;
+    static _erelocate: u8;
+    static _szero: u8;
+    static _ezero: u8;
+}
+
+#[cfg(target_arch = "arm")]
+fn size(start: &u8, end: &u8) -> usize {
+    (end as *const u8 as usize).saturating_sub(start as *const u8 as usize)
+}
+
+/// Reports the sizes of `.text` (with the read-only data), `.data` and
+/// `.bss` in bytes.
+#[cfg(target_arch = "arm")]
+pub fn report(sink: &mut dyn ResultSink) {
+    let (text, data, bss) = unsafe {
+        (
+            size(&_sfixed, &_efixed),
+            size(&_srelocate, &_erelocate),
+            size(&_szero, &_ezero),
+        )
+    };
+    sink.metadata("text_bytes", &format!("{}", text));
+    sink.metadata("data_bytes", &format!("{}", data));
+    sink.metadata("bss_bytes", &format!("{}", bss));
+}
+
+#[cfg(not(target_arch = "arm"))]
+pub fn report(sink: &mut dyn ResultSink) {
+    sink.metadata("text_bytes", "unknown");
+    sink.metadata("data_bytes", "unknown");
+    sink.metadata("bss_bytes", "unknown");
+}
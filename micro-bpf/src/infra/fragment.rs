@@ -0,0 +1,81 @@
+//! Fragments the heap before the benchmark, the state of a host which has
+//! been running for a while. `FRAGMENT_HEAP_KB` of small blocks are allocated
+//! and every other one is freed again, leaving holes too small for the VM and
+//! the JIT buffer between blocks which stay allocated until the end.
+
+use alloc::alloc::{alloc, dealloc, Layout};
+use alloc::format;
+use alloc::vec::Vec;
+
+use super::allocator;
+use super::config;
+use crate::output::ResultSink;
+
+/// Size of the blocks, below the allocations of a program load.
+const BLOCK_SIZE: usize = 32;
+
+/// The blocks kept allocated, freed on drop.
+pub struct Fragments {
+    blocks: Vec<*mut u8>,
+}
+
+impl Drop for Fragments {
+    fn drop(&mut self) {
+        for block in self.blocks.iter() {
+            unsafe { dealloc(*block, layout()) };
+        }
+    }
+}
+
+fn layout() -> Layout {
+    Layout::from_size_align(BLOCK_SIZE, 8).unwrap()
+}
+
+/// Fragments the heap and reports the fragmentation and whether the
+/// allocations of a load still fit. `jit_buffer` is the size of the JIT's
+/// buffer, if there is one. The heap stays fragmented while the result is
+/// alive.
+pub fn fragment(sink: &mut dyn ResultSink, jit_buffer: Option<usize>) -> Fragments {
+    let largest_before = allocator::largest_block();
+    let count = config::fragment_heap_kb() * 1024 / BLOCK_SIZE;
+
+    let mut allocated = Vec::with_capacity(count);
+    for _ in 0..count {
+        let block = unsafe { alloc(layout()) };
+        if block.is_null() {
+            break;
+        }
+        allocated.push(block);
+    }
+    let mut blocks = Vec::with_capacity(allocated.len() / 2);
+    let mut hole_bytes = 0;
+    for (i, block) in allocated.into_iter().enumerate() {
+        if i % 2 == 0 {
+            blocks.push(block);
+        } else {
+            unsafe { dealloc(block, layout()) };
+            hole_bytes += BLOCK_SIZE;
+        }
+    }
+
+    // External fragmentation: the share of the free memory that is not in
+    // the largest free block. The holes and the largest block are taken as
+    // the free memory, the allocator can't be asked for its total.
+    let largest_after = allocator::largest_block();
+    let free = largest_after + hole_bytes;
+    let fragmentation_pct = if free > 0 { hole_bytes * 100 / free } else { 0 };
+    sink.metadata(
+        "fragment_heap",
+        &format!(
+            "blocks={} block_size={} hole_bytes={} largest_before={} largest_after={} fragmentation_pct={}",
+            blocks.len(),
+            BLOCK_SIZE,
+            hole_bytes,
+            largest_before,
+            largest_after,
+            fragmentation_pct
+        ),
+    );
+    sink.metadata("fragment_heap_allocations", &allocator::load_allocations(jit_buffer));
+    Fragments { blocks }
+}
@@ -0,0 +1,132 @@
+//! Measures how much the benchmark delays a time-critical application thread.
+//!
+//! A thread with a higher priority than main is woken by ztimer every
+//! `PERIOD_MS` and records how late it actually runs. The latencies are
+//! stored in a preallocated buffer, the thread never prints or allocates, so
+//! it doesn't influence the benchmark beyond what it measures.
+
+use alloc::format;
+use alloc::vec::Vec;
+use portable_atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+
+use crate::infra::config;
+use crate::output::ResultSink;
+use crate::util::stats::{percentile, Accumulator};
+use crate::util::time::now_us;
+
+/// Latencies stored per phase, later wake-ups are only counted.
+const MAX_SAMPLES: usize = 1024;
+/// Length of the control phase before the benchmark, in periods.
+const CONTROL_PERIODS: u32 = 100;
+const STACK_SIZE: usize = 1024;
+
+static mut STACK: [u8; STACK_SIZE] = [0; STACK_SIZE];
+const ZERO: AtomicU32 = AtomicU32::new(0);
+static SAMPLES: [AtomicU32; MAX_SAMPLES] = [ZERO; MAX_SAMPLES];
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+static RECORDING: AtomicBool = AtomicBool::new(false);
+static PERIOD_US: AtomicU32 = AtomicU32::new(0);
+
+/// Latency statistics of one phase.
+pub struct Latencies {
+    pub wakeups: usize,
+    pub min_us: u32,
+    pub mean_us: u32,
+    pub p99_us: u32,
+    pub max_us: u32,
+}
+
+/// Starts the periodic thread. It runs until the firmware ends, but only
+/// records between `start` and `stop`.
+pub fn spawn() {
+    PERIOD_US.store(config::period_ms() * 1000, Ordering::Relaxed);
+
+    let priority = riot_wrappers::thread::get_pid()
+        .priority()
+        .expect("main thread has no priority");
+    assert!(priority > 0, "main already runs at the highest priority");
+
+    unsafe {
+        riot_sys::thread_create(
+            core::ptr::addr_of_mut!(STACK) as *mut _,
+            STACK_SIZE as _,
+            // Lower values have a higher priority
+            priority - 1,
+            0,
+            Some(periodic),
+            core::ptr::null_mut(),
+            "interference\0".as_ptr() as *const _,
+        );
+    }
+}
+
+/// Measures the latencies while main is idle, as the reference for the
+/// latencies during the benchmark.
+pub fn control() -> Latencies {
+    start();
+    unsafe {
+        riot_sys::ztimer_sleep(riot_sys::ZTIMER_USEC, CONTROL_PERIODS * PERIOD_US.load(Ordering::Relaxed));
+    }
+    stop()
+}
+
+/// Discards the recorded latencies and starts recording.
+pub fn start() {
+    COUNT.store(0, Ordering::Relaxed);
+    RECORDING.store(true, Ordering::Release);
+}
+
+/// Stops recording and evaluates the latencies since `start`.
+pub fn stop() -> Latencies {
+    RECORDING.store(false, Ordering::Release);
+    let wakeups = COUNT.load(Ordering::Acquire);
+    let mut samples: Vec<u32> = SAMPLES[..wakeups.min(MAX_SAMPLES)]
+        .iter()
+        .map(|s| s.load(Ordering::Relaxed))
+        .collect();
+
+    let mut acc = Accumulator::new();
+    samples.iter().for_each(|s| acc.add(*s));
+    Latencies {
+        wakeups,
+        min_us: acc.min().unwrap_or(0),
+        mean_us: acc.mean().unwrap_or(0),
+        p99_us: percentile(&mut samples, 99).unwrap_or(0),
+        max_us: acc.max().unwrap_or(0),
+    }
+}
+
+pub fn report(sink: &mut dyn ResultSink, phase: &str, latencies: &Latencies) {
+    sink.comment(&format!(
+        "interference phase={} period_ms={} wakeups={} stored={} min_us={} mean_us={} p99_us={} max_us={}",
+        phase,
+        config::period_ms(),
+        latencies.wakeups,
+        latencies.wakeups.min(MAX_SAMPLES),
+        latencies.min_us,
+        latencies.mean_us,
+        latencies.p99_us,
+        latencies.max_us
+    ));
+}
+
+unsafe extern "C" fn periodic(_arg: *mut core::ffi::c_void) -> *mut core::ffi::c_void {
+    let period = PERIOD_US.load(Ordering::Relaxed);
+    let mut deadline = now_us().wrapping_add(period);
+    loop {
+        let remaining = deadline.wrapping_sub(now_us());
+        // Skip the sleep if the previous wake-up was already later than a
+        // whole period, the latency is then recorded right away.
+        if (remaining as i32) > 0 {
+            riot_sys::ztimer_sleep(riot_sys::ZTIMER_USEC, remaining);
+        }
+        let latency = now_us().wrapping_sub(deadline);
+        if RECORDING.load(Ordering::Acquire) {
+            let index = COUNT.fetch_add(1, Ordering::AcqRel);
+            if index < MAX_SAMPLES {
+                SAMPLES[index].store(latency, Ordering::Relaxed);
+            }
+        }
+        deadline = deadline.wrapping_add(period);
+    }
+}
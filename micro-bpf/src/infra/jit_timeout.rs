@@ -0,0 +1,46 @@
+//! Bounds the JIT compilation with `JIT_COMPILE_TIMEOUT_MS`. The compiler
+//! can't be interrupted and checks no flag, a ztimer callback reboots the
+//! board instead once the timeout expires, after printing a banner on the
+//! console. An automated run stops at the banner rather than hanging on a
+//! pathological program.
+
+use core::mem::MaybeUninit;
+use core::ptr::addr_of_mut;
+use riot_wrappers::println;
+
+use super::config;
+use crate::output::{ResultSink, StdioSink};
+
+static mut TIMER: MaybeUninit<riot_sys::ztimer_t> = MaybeUninit::zeroed();
+
+fn timer() -> *mut riot_sys::ztimer_t {
+    unsafe { addr_of_mut!(TIMER).cast() }
+}
+
+/// Runs in the ISR. The compilation is stuck on the main thread, the console
+/// is free. The heap may be locked by the compiler, the comment is printed
+/// without formatting it into a String first.
+unsafe extern "C" fn fire(_arg: *mut core::ffi::c_void) {
+    StdioSink.banner("JIT compile timeout");
+    println!(
+        "# jit_compile_timeout timeout_ms={} the compilation didn't finish, rebooting",
+        config::jit_compile_timeout_ms()
+    );
+    riot_sys::pm_reboot();
+}
+
+/// Starts the timeout, right before the compilation.
+pub fn arm() {
+    unsafe {
+        (*timer()).callback = Some(fire);
+        (*timer()).arg = core::ptr::null_mut();
+        riot_sys::ztimer_set(riot_sys::ZTIMER_USEC, timer(), config::jit_compile_timeout_ms() * 1000);
+    }
+}
+
+/// Stops the timeout once the compilation returned.
+pub fn disarm() {
+    unsafe {
+        riot_sys::ztimer_remove(riot_sys::ZTIMER_USEC, timer());
+    }
+}
@@ -0,0 +1,48 @@
+//! Loading a program into an interpreter VM, apart from the timing loop:
+//! construction of the VM, registration of the helpers the program calls and
+//! verification.
+
+use rbpf::{Error, InterpreterVariant};
+use riot_wrappers::ztimer::Clock;
+
+use super::vm::{Vm, VmKind};
+use crate::middleware::helpers::{register_helpers, HelperFunction};
+use crate::util::time::Elapsed;
+
+/// A program loaded into a VM, ready to be executed.
+pub struct LoadedProgram<'a> {
+    pub vm: Vm<'a>,
+    /// Time to register the helpers, part of the load.
+    pub register_us: u32,
+    /// Whether the program passed the verifier. Programs which didn't must
+    /// not be executed.
+    pub verified: bool,
+}
+
+/// Loads `prog` into a VM of `kind`, registers `helpers`, the helpers the
+/// program calls found by the analysis beforehand, and verifies it. Without
+/// `register` no helpers are registered, a program calling them fails the
+/// verification then.
+pub fn load_program<'a>(
+    prog: &'a [u8],
+    variant: InterpreterVariant,
+    kind: VmKind,
+    helpers: &[HelperFunction],
+    register: bool,
+) -> Result<LoadedProgram<'a>, Error> {
+    let mut vm = Vm::new(kind, prog, variant)?;
+    let register_us = Clock::usec()
+        .time(|| {
+            if register {
+                register_helpers(&mut vm, helpers);
+                #[cfg(feature = "vfs-helpers")]
+                for (id, function) in crate::middleware::vfs::HELPERS.iter() {
+                    vm.register_raw_helper(*id, *function);
+                }
+            }
+        })
+        .expect("failed to measure register helpers time")
+        .us();
+    let verified = vm.verify_loaded_program();
+    Ok(LoadedProgram { vm, register_us, verified })
+}
@@ -0,0 +1,33 @@
+//! Compares the memory two executions of the program leave behind, not only
+//! their return values: with the JIT the JITted code and the interpreter,
+//! otherwise the two load formats of header-cost. The second execution, the
+//! interpreter or the raw object file variant, runs on a restored copy of
+//! the memory the measured one started with.
+
+use alloc::vec::Vec;
+
+use crate::util::checksum::fnv1a;
+
+/// Copies the memory. The program writes it through raw pointers, the
+/// compiler must not assume it unchanged.
+pub fn snapshot(memory: &[u8]) -> Vec<u8> {
+    memory.iter().map(|byte| unsafe { core::ptr::read_volatile(byte) }).collect()
+}
+
+/// Writes `bytes` back into the memory, which the program may write as well.
+pub fn restore(memory: &mut [u8], bytes: &[u8]) {
+    for (target, byte) in memory.iter_mut().zip(bytes) {
+        unsafe { core::ptr::write_volatile(target, *byte) };
+    }
+}
+
+/// Offset of the first byte of the memory that differs from `expected`,
+/// `None` if their checksums match. The bytes are only searched on a
+/// mismatch.
+pub fn first_difference(memory: &[u8], expected: &[u8]) -> Option<usize> {
+    let actual = snapshot(memory);
+    if fnv1a(&actual) == fnv1a(expected) {
+        return None;
+    }
+    actual.iter().zip(expected.iter()).position(|(a, b)| a != b)
+}
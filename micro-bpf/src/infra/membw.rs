@@ -0,0 +1,76 @@
+//! Memory bandwidth probe, run every `MEMBW_EVERY` iterations between the
+//! timed iterations. A memcpy and a memset over a fixed buffer give the speed
+//! of the board's memory, to put the execution times of boards with very
+//! different memory subsystems into relation.
+
+use alloc::format;
+use core::ptr::{addr_of, addr_of_mut};
+use riot_wrappers::ztimer::Clock;
+
+use crate::output::ResultSink;
+use crate::util::time::Elapsed;
+
+/// Size of both buffers. Small enough for the smallest boards, large enough
+/// that a pass takes well above the timer resolution.
+const BUFFER_SIZE: usize = 4096;
+/// Passes over the buffer per measurement.
+const PASSES: usize = 16;
+
+static mut SOURCE: [u8; BUFFER_SIZE] = [0xa5; BUFFER_SIZE];
+static mut DESTINATION: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
+
+/// Throughput of one probe in MB/s, i.e. bytes per microsecond.
+pub struct Bandwidth {
+    pub copy_mbps: u32,
+    pub set_mbps: u32,
+}
+
+pub fn measure() -> Bandwidth {
+    let micro_sec = Clock::usec();
+    let bytes = (BUFFER_SIZE * PASSES) as u32;
+
+    let copy_us = micro_sec
+        .time(|| {
+            for _ in 0..PASSES {
+                unsafe {
+                    core::ptr::copy_nonoverlapping(
+                        addr_of!(SOURCE) as *const u8,
+                        addr_of_mut!(DESTINATION) as *mut u8,
+                        BUFFER_SIZE,
+                    );
+                    core::hint::black_box(addr_of!(DESTINATION));
+                }
+            }
+        })
+        .expect("failed to measure the memcpy")
+        .us();
+
+    let set_us = micro_sec
+        .time(|| {
+            for pass in 0..PASSES {
+                unsafe {
+                    core::ptr::write_bytes(addr_of_mut!(DESTINATION) as *mut u8, pass as u8, BUFFER_SIZE);
+                    core::hint::black_box(addr_of!(DESTINATION));
+                }
+            }
+        })
+        .expect("failed to measure the memset")
+        .us();
+
+    Bandwidth {
+        copy_mbps: bytes / copy_us.max(1),
+        set_mbps: bytes / set_us.max(1),
+    }
+}
+
+/// Runs the probe and reports it, `run` is the iteration it follows.
+pub fn report(sink: &mut dyn ResultSink, run: usize) {
+    let bandwidth = measure();
+    sink.comment(&format!(
+        "membw after_run={} bytes={} membw_mbps={} memset_mbps={}",
+        run,
+        BUFFER_SIZE * PASSES,
+        bandwidth.copy_mbps,
+        bandwidth.set_mbps
+    ));
+}
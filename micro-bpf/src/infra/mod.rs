@@ -1 +1,71 @@
+#[cfg(feature = "align-prog")]
+pub mod align;
 pub mod allocator;
+#[cfg(feature = "base64-load")]
+pub mod base64_load;
+pub mod baseline;
+#[cfg(test)]
+mod baseline_file;
+#[cfg(feature = "stop-at-insn")]
+pub mod breakpoint;
+#[cfg(feature = "multithread")]
+pub mod concurrent;
+pub mod config;
+#[cfg(feature = "dump-context")]
+pub mod context_dump;
+#[cfg(feature = "encodings")]
+pub mod encodings;
+pub mod error;
+pub mod footprint;
+#[cfg(feature = "fragment-heap")]
+pub mod fragment;
+#[cfg(feature = "interference")]
+pub mod interference;
+#[cfg(feature = "jit-timeout")]
+pub mod jit_timeout;
+#[cfg(not(feature = "jit"))]
+pub mod load;
+#[cfg(feature = "membw")]
+pub mod membw;
+#[cfg(feature = "compare-mem")]
+pub mod mem_compare;
+#[cfg(feature = "reset-peripherals")]
+pub mod peripherals;
+#[cfg(feature = "placement")]
+pub mod placement;
+#[cfg(feature = "pmu")]
+pub mod pmu;
+#[cfg(feature = "prewarm-mbuff")]
+pub mod prewarm;
+#[cfg(feature = "reference")]
+pub mod reference;
+#[cfg(feature = "register-overhead")]
+pub mod register_overhead;
+#[cfg(feature = "reserve-heap")]
+pub mod reserve;
+#[cfg(feature = "no-dcache")]
+pub mod dcache;
+#[cfg(feature = "dump-samples")]
+pub mod samples;
+#[cfg(feature = "seeds")]
+pub mod seeds;
+#[cfg(feature = "signed-programs")]
+pub mod signing;
+#[cfg(feature = "canary-check")]
+pub mod stack;
+pub mod summary;
+#[cfg(feature = "multithread")]
+pub mod sync;
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(feature = "suite")]
+pub mod suite;
+#[cfg(feature = "load-throughput")]
+pub mod throughput;
+#[cfg(feature = "timer-selftest")]
+pub mod timer_selftest;
+#[cfg(feature = "trigger")]
+pub mod trigger;
+#[cfg(feature = "unwrap")]
+pub mod unwrap;
+pub mod vm;
@@ -0,0 +1,75 @@
+//! Puts the peripherals listed in `RESET_PERIPHERALS` back into a known state
+//! before every iteration, outside of the timed regions. Helpers with side
+//! effects on them would otherwise leave state for the next iteration.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use riot_wrappers::gpio;
+
+use crate::output::ResultSink;
+
+#[derive(Copy, Clone)]
+pub enum Peripheral {
+    /// `gpio:PORT.PIN`, configured as an output and cleared.
+    Gpio { port: u32, pin: u32 },
+}
+
+impl Peripheral {
+    pub fn parse(name: &str) -> Option<Self> {
+        let (port, pin) = name.strip_prefix("gpio:")?.split_once('.')?;
+        Some(Peripheral::Gpio {
+            port: port.parse().ok()?,
+            pin: pin.parse().ok()?,
+        })
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Peripheral::Gpio { port, pin } => format!("gpio:{}.{}", port, pin),
+        }
+    }
+
+    /// Returns false if the peripheral doesn't exist on the board.
+    fn reset(&self) -> bool {
+        match *self {
+            Peripheral::Gpio { port, pin } => {
+                let gpio = match gpio::GPIO::from_c(unsafe { riot_sys::macro_GPIO_PIN(port, pin) }) {
+                    Some(gpio) => gpio,
+                    None => return false,
+                };
+                match gpio.configure_as_output(gpio::OutputMode::Out) {
+                    Ok(out_pin) => {
+                        unsafe { riot_sys::gpio_clear(out_pin.to_c()) };
+                        true
+                    }
+                    Err(_) => false,
+                }
+            }
+        }
+    }
+}
+
+/// Resets every listed peripheral once and reports them, the ones which
+/// can't be reset are refused before the benchmark starts.
+pub fn check(sink: &mut dyn ResultSink, peripherals: &[Peripheral]) -> bool {
+    let names: Vec<String> = peripherals.iter().map(Peripheral::name).collect();
+    sink.metadata("reset_peripherals", &names.join(","));
+    match peripherals.iter().find(|peripheral| !peripheral.reset()) {
+        Some(peripheral) => {
+            sink.comment(&format!(
+                "reset_peripherals=refused {} can't be reset on this board",
+                peripheral.name()
+            ));
+            false
+        }
+        None => true,
+    }
+}
+
+/// Resets the peripherals, at the start of each iteration.
+pub fn reset(peripherals: &[Peripheral]) {
+    for peripheral in peripherals {
+        peripheral.reset();
+    }
+}
@@ -0,0 +1,42 @@
+//! Whether the benchmark code and the program bytes are in RAM or executed in
+//! place from flash, which changes the timings considerably on boards that
+//! support both. The memory map comes from the symbols of RIOT's Cortex-M
+//! linker script.
+
+use alloc::boxed::Box;
+
+use crate::output::ResultSink;
+
+extern "C" {
+    static _srom: u8;
+    static _erom: u8;
+    static _sram: u8;
+    static _eram: u8;
+}
+
+fn region(address: usize) -> &'static str {
+    let (rom, ram) = unsafe {
+        (
+            (&_srom as *const u8 as usize)..(&_erom as *const u8 as usize),
+            (&_sram as *const u8 as usize)..(&_eram as *const u8 as usize),
+        )
+    };
+    if ram.contains(&address) {
+        "yes"
+    } else if rom.contains(&address) {
+        "no"
+    } else {
+        "unknown"
+    }
+}
+
+/// Reports where the benchmark code and `prog` are. The JIT compiles into a
+/// buffer on the heap, a fresh allocation stands in for it.
+pub fn report(sink: &mut dyn ResultSink, prog: &[u8]) {
+    sink.metadata("code_in_ram", region(report as *const () as usize));
+    sink.metadata("prog_in_ram", region(prog.as_ptr() as usize));
+    if cfg!(feature = "jit") {
+        let probe = Box::new(0u8);
+        sink.metadata("jit_code_in_ram", region(&*probe as *const u8 as usize));
+    }
+}
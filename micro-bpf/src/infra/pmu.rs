@@ -0,0 +1,84 @@
+//! Event counters of the Armv8.1-M performance monitoring unit (Cortex-M55,
+//! Cortex-M85), sampled around the execution. The events are configured with
+//! `PMU_EVENTS` (see build.rs), on cores without a PMU the columns are "na".
+
+use crate::infra::config;
+
+include!(concat!(env!("OUT_DIR"), "/pmu.rs"));
+
+pub const MAX_COUNTERS: usize = 4;
+
+const PMU_BASE: usize = 0xE000_3000;
+const EVCNTR: usize = PMU_BASE;
+const EVTYPER: usize = PMU_BASE + 0x400;
+const CNTENSET: usize = PMU_BASE + 0xC00;
+const OVSCLR: usize = PMU_BASE + 0xC80;
+const OVSSET: usize = PMU_BASE + 0xCC0;
+const CTRL: usize = PMU_BASE + 0xE04;
+const CTRL_ENABLE: u32 = 1 << 0;
+const CTRL_EVENT_RESET: u32 = 1 << 1;
+/// The PMU is only clocked with the trace enabled in DEMCR.
+const DEMCR: usize = 0xE000_EDFC;
+const DEMCR_TRCENA: u32 = 1 << 24;
+
+/// The counters of one execution.
+#[derive(Copy, Clone)]
+pub struct Sample {
+    pub counts: [u32; MAX_COUNTERS],
+    /// A counter wrapped, its count is meaningless.
+    pub overflowed: bool,
+}
+
+fn read(address: usize) -> u32 {
+    unsafe { core::ptr::read_volatile(address as *const u32) }
+}
+
+fn write(address: usize, value: u32) {
+    unsafe { core::ptr::write_volatile(address as *mut u32, value) }
+}
+
+fn mask() -> u32 {
+    (1 << EVENTS.len()) - 1
+}
+
+pub fn available() -> bool {
+    config::pmu_present()
+}
+
+/// Programs the events, once before the benchmark. Returns false if the
+/// core has no PMU.
+pub fn configure() -> bool {
+    if !available() {
+        return false;
+    }
+    write(DEMCR, read(DEMCR) | DEMCR_TRCENA);
+    for (counter, event) in EVENTS.iter().enumerate() {
+        write(EVTYPER + 4 * counter, *event as u32);
+    }
+    write(CNTENSET, mask());
+    write(CTRL, CTRL_ENABLE);
+    true
+}
+
+/// Clears the counters and their overflow flags.
+pub fn start() {
+    if available() {
+        write(OVSCLR, mask());
+        write(CTRL, CTRL_ENABLE | CTRL_EVENT_RESET);
+    }
+}
+
+/// Reads the counters, `None` without a PMU.
+pub fn stop() -> Option<Sample> {
+    if !available() {
+        return None;
+    }
+    let mut counts = [0; MAX_COUNTERS];
+    for (counter, count) in counts.iter_mut().take(EVENTS.len()).enumerate() {
+        *count = read(EVCNTR + 4 * counter);
+    }
+    Some(Sample {
+        counts,
+        overflowed: read(OVSSET) & mask() != 0,
+    })
+}
@@ -0,0 +1,31 @@
+//! Reads the whole mbuff, and the region buffer it points to, right before
+//! the timed execution, so that the program finds its input in the data
+//! cache. With `MBUFF_PREWARM=alternate` only every other recorded iteration
+//! is prewarmed, one run then shows the cost of the first touch.
+
+use portable_atomic::{AtomicUsize, Ordering};
+
+/// Iterations since the recorded ones started, the warmup iterations are
+/// counted as well but the count is reset before the recorded ones.
+static ITERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Starts the alternation with a prewarmed iteration.
+pub fn reset() {
+    ITERATION.store(0, Ordering::Relaxed);
+}
+
+/// Whether the memory of the next iteration is prewarmed, `alternate` is
+/// `Config::mbuff_prewarm_alternate`.
+pub fn due(alternate: bool) -> bool {
+    let iteration = ITERATION.fetch_add(1, Ordering::Relaxed);
+    !alternate || iteration & 1 == 0
+}
+
+/// Reads every byte of `value`. The reads are volatile, the compiler must
+/// not drop them because their result is unused.
+pub fn touch<T: ?Sized>(value: &T) {
+    let start = value as *const T as *const u8;
+    for i in 0..core::mem::size_of_val(value) {
+        unsafe { core::ptr::read_volatile(start.add(i)) };
+    }
+}
@@ -0,0 +1,116 @@
+//! Native reference implementation of the workload, run once at startup on
+//! the same input the eBPF program gets. Its result is the expected result
+//! of the correctness check instead of a hand-written `EXPECTED`.
+//!
+//! Only ebpf/libud.c has an input (the Context), the port below follows it
+//! statement by statement, with `long` as i64 and `int` as i32.
+
+use portable_atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::Context;
+
+const SCALE_FACTOR: &str = env!("SCALE_FACTOR");
+
+const X_REF: [i64; 20] = [0, 0, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+static EXPECTED: AtomicU64 = AtomicU64::new(0);
+static COMPUTED: AtomicBool = AtomicBool::new(false);
+
+/// Runs the reference once and keeps its result for `expected`.
+pub fn compute() -> u64 {
+    let mut ctx = Context::new();
+    let expected = libud(&mut ctx, scale_factor());
+    EXPECTED.store(expected, Ordering::Relaxed);
+    COMPUTED.store(true, Ordering::Release);
+    expected
+}
+
+/// The result computed by `compute`.
+pub fn expected() -> u64 {
+    assert!(COMPUTED.load(Ordering::Acquire), "the reference result wasn't computed yet");
+    EXPECTED.load(Ordering::Relaxed)
+}
+
+/// The result for the input of a single execution, e.g. the Context of a
+/// seed.
+pub fn expected_of(mut ctx: Context) -> u64 {
+    libud(&mut ctx, scale_factor())
+}
+
+fn scale_factor() -> u32 {
+    SCALE_FACTOR
+        .parse()
+        .unwrap_or_else(|_| panic!("Failed to parse SCALE_FACTOR={}", SCALE_FACTOR))
+}
+
+fn sdiv(dividend: i64, divisor: i64) -> i64 {
+    if divisor == 0 {
+        return 0;
+    }
+    let negative = (dividend < 0) ^ (divisor < 0);
+    let result = (dividend.unsigned_abs() / divisor.unsigned_abs()) as i64;
+    if negative {
+        result.wrapping_neg()
+    } else {
+        result
+    }
+}
+
+fn libud(ctx: &mut Context, scale_factor: u32) -> u64 {
+    let chkerr = 0;
+    let n = 5;
+
+    for _ in 0..scale_factor {
+        let a = &mut ctx.a;
+        for i in 0..=n {
+            let mut w = 0i64;
+            for j in 0..=n {
+                a[i][j] = (i as i64 + 1) + (j as i64 + 1);
+                if i == j {
+                    a[i][j] *= 2;
+                }
+                w = w.wrapping_add(a[i][j]);
+            }
+            ctx.b[i] = w;
+        }
+
+        for i in 0..n {
+            for j in i + 1..=n {
+                let mut w = a[j][i];
+                for k in 0..i {
+                    w = w.wrapping_sub(a[j][k].wrapping_mul(a[k][i]));
+                }
+                a[j][i] = sdiv(w, a[i][i]);
+            }
+            for j in i + 1..=n {
+                let mut w = a[i + 1][j];
+                for k in 0..=i {
+                    w = w.wrapping_sub(a[i + 1][k].wrapping_mul(a[k][j]));
+                }
+                a[i + 1][j] = w;
+            }
+        }
+
+        let y = &mut ctx.y;
+        y[0] = ctx.b[0];
+        for i in 1..=n {
+            let mut w = ctx.b[i];
+            for j in 0..i {
+                w = w.wrapping_sub(a[i][j].wrapping_mul(y[j]));
+            }
+            y[i] = w;
+        }
+
+        let x = &mut ctx.x;
+        x[n] = sdiv(y[n], a[n][n]);
+        for i in (0..n).rev() {
+            let mut w = y[i];
+            for j in i + 1..=n {
+                w = w.wrapping_sub(a[i][j].wrapping_mul(x[j]));
+            }
+            x[i] = sdiv(w, a[i][i]);
+        }
+    }
+
+    (ctx.x == X_REF && chkerr == 0) as u64
+}
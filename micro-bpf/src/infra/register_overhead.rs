@@ -0,0 +1,42 @@
+//! What registering the helpers in every iteration costs compared to
+//! registering them once into a VM that is reused. When a program starts,
+//! all helpers are registered once into a VM of the current kind, as a VM
+//! loaded once would be. Every iteration then registers them again into a
+//! fresh VM, outside of the timed load. The overhead of an iteration is its
+//! registration less its share of the one registration, the time a
+//! loaded-once VM would save.
+
+use portable_atomic::{AtomicU32, Ordering};
+use riot_wrappers::ztimer::Clock;
+
+use super::vm::{self, Vm};
+use crate::middleware::helpers::register_all;
+use crate::util::time::Elapsed;
+
+/// Time of the registration into the VM of the program.
+static ONCE_US: AtomicU32 = AtomicU32::new(0);
+
+/// Time to construct a VM for `prog` and register all helpers into it,
+/// only the registration is timed.
+fn register_fresh_us(prog: &[u8]) -> u32 {
+    let mut vm = Vm::new(vm::current(), prog, rbpf::InterpreterVariant::FemtoContainersHeader)
+        .expect("failed to construct a VM for the registration");
+    Clock::usec()
+        .time(|| register_all(&mut vm))
+        .expect("failed to measure register helpers time")
+        .us()
+}
+
+/// Registers the helpers of the VM that is reused, when the program starts.
+pub fn register_once(prog: &[u8]) {
+    ONCE_US.store(register_fresh_us(prog), Ordering::Relaxed);
+}
+
+/// The registration into a fresh VM of an iteration and its overhead, the
+/// one registration is shared by `iterations`, see
+/// `Config::planned_iterations`.
+pub fn measure(prog: &[u8], iterations: usize) -> (u32, i64) {
+    let fresh_us = register_fresh_us(prog);
+    let share_us = ONCE_US.load(Ordering::Relaxed) / iterations.max(1) as u32;
+    (fresh_us, fresh_us as i64 - share_us as i64)
+}
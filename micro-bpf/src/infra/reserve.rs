@@ -0,0 +1,52 @@
+//! Holds `RESERVE_HEAP_BYTES` of the heap in one block during the benchmark,
+//! so that the VM runs with little but contiguous memory left. Unlike
+//! fragment-heap the remaining memory is one block, the loads fail once it
+//! is too small rather than because of holes.
+
+use alloc::alloc::{alloc, dealloc, Layout};
+use alloc::format;
+
+use super::allocator;
+use super::config;
+use crate::output::ResultSink;
+
+/// The reserved block, released on drop.
+pub struct Reservation {
+    block: *mut u8,
+    layout: Layout,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        unsafe { dealloc(self.block, self.layout) };
+    }
+}
+
+/// Reserves the block and reports the memory left and whether the
+/// allocations of a load still fit. `jit_buffer` is the size of the JIT's
+/// buffer, if there is one. `None` if the heap has no block of the requested
+/// size, the benchmark doesn't run then.
+pub fn reserve(sink: &mut dyn ResultSink, jit_buffer: Option<usize>) -> Option<Reservation> {
+    let bytes = config::reserve_heap_bytes();
+    let largest_before = allocator::largest_block();
+    let layout = Layout::from_size_align(bytes.max(1), 8).expect("RESERVE_HEAP_BYTES is too large");
+    let block = unsafe { alloc(layout) };
+    if block.is_null() {
+        sink.comment(&format!(
+            "reserve_heap=refused no block of {} bytes, the largest is {}",
+            bytes, largest_before
+        ));
+        return None;
+    }
+    sink.metadata(
+        "reserve_heap",
+        &format!(
+            "bytes={} largest_before={} largest_after={}",
+            bytes,
+            largest_before,
+            allocator::largest_block()
+        ),
+    );
+    sink.metadata("reserve_heap_allocations", &allocator::load_allocations(jit_buffer));
+    Some(Reservation { block, layout })
+}
@@ -0,0 +1,56 @@
+//! Dumps every load and execution time of a program once after its rows, so
+//! that the host can reconstruct the exact time series without parsing the
+//! CSV. The times are buffered for the whole program, the buffers are
+//! allocated when the program starts.
+//!
+//! The dump is one comment, the two arrays (load_program_us, then
+//! execution_time_us, in the order of the iterations) CBOR encoded like the
+//! records of `CBOR_RESULTS` and printed as hex. Each array starts with its
+//! length.
+
+use alloc::format;
+use alloc::string::String;
+use core::fmt::Write;
+
+use super::allocator;
+use super::config;
+use crate::output::ResultSink;
+use crate::util::cbor::Encoder;
+
+/// Warns if the heap can't hold the buffers of a program.
+pub fn check(sink: &mut dyn ResultSink, name: &str) {
+    let samples = config::iterations() * config::concurrent_vms();
+    let required = samples * core::mem::size_of::<u32>();
+    let available = allocator::largest_block();
+    // Both buffers are allocated, the second one might need a block as
+    // large as the first
+    if 2 * required > available {
+        sink.banner("DUMP SAMPLES WARNING");
+        sink.comment(&format!(
+            "dump_samples program={} samples={} buffer_bytes=2x{} largest_block={} the buffers may not fit, lower ITERATIONS",
+            name, samples, required, available
+        ));
+    }
+}
+
+/// Prints the dump of a program.
+pub fn dump(sink: &mut dyn ResultSink, name: &str, load_program_us: &[u32], execution_time_us: &[u32]) {
+    let mut encoder = Encoder::new();
+    for samples in [load_program_us, execution_time_us].iter() {
+        encoder.array(samples.len());
+        for sample in samples.iter() {
+            encoder.uint(*sample as u64);
+        }
+    }
+    let bytes = encoder.into_bytes();
+    let mut hex = String::with_capacity(2 * bytes.len());
+    for byte in bytes.iter() {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    sink.comment(&format!(
+        "dump_samples program={} samples={} arrays=load_program_us,execution_time_us cbor={}",
+        name,
+        execution_time_us.len(),
+        hex
+    ));
+}
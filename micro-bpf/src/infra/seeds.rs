@@ -0,0 +1,57 @@
+//! Runs the iterations on the inputs of a fixed list of seeds from `SEEDS`, one
+//! recorded iteration per seed, so that the same inputs are benchmarked
+//! whatever the iteration count of the run. The Context of an iteration is
+//! filled from its seed, the native reference computes the expected result
+//! of the same Context.
+//!
+//! The warmup iterations take the seeds from the start of the list as well,
+//! the recorded ones start over with the first seed.
+
+use portable_atomic::{AtomicUsize, Ordering};
+
+use crate::Context;
+
+/// Iterations since the recorded ones started, the warmup iterations are
+/// counted as well but the count is reset before the recorded ones.
+static ITERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Starts over with the first seed.
+pub fn reset() {
+    ITERATION.store(0, Ordering::Relaxed);
+}
+
+/// The seed of the next iteration out of `Config::seeds`, the list repeats
+/// once it is exhausted.
+pub fn next(seeds: &[u64]) -> u64 {
+    seeds[ITERATION.fetch_add(1, Ordering::Relaxed) % seeds.len()]
+}
+
+/// xorshift64*, a seed of 0 is mapped to another value as it would only
+/// produce zeros.
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next(&mut self) -> i64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 32) as i64
+    }
+}
+
+/// The Context of the iterations with this seed.
+pub fn context(seed: u64) -> Context {
+    let mut random = Xorshift(if seed == 0 { 0x9e37_79b9_7f4a_7c15 } else { seed });
+    let mut ctx = Context::new();
+    for value in ctx
+        .a
+        .iter_mut()
+        .flatten()
+        .chain(ctx.b.iter_mut())
+        .chain(ctx.x.iter_mut())
+        .chain(ctx.y.iter_mut())
+    {
+        *value = random.next();
+    }
+    ctx
+}
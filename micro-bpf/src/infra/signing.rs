@@ -0,0 +1,26 @@
+//! Ed25519 signatures of the embedded programs, checked before a program is
+//! loaded like a SUIT deployment does before installing it. The public key
+//! and the signatures are embedded by build.rs, see scripts/sign_program.py.
+
+use ed25519_compact::{PublicKey, Signature};
+
+pub struct ProgramSignature {
+    pub program: &'static [u8],
+    pub signature: [u8; 64],
+}
+
+include!(concat!(env!("OUT_DIR"), "/signatures.rs"));
+
+/// The signature embedded for the program with these bytes.
+pub fn signature_of(prog: &[u8]) -> Option<&'static [u8; 64]> {
+    SIGNATURES
+        .iter()
+        .find(|entry| entry.program == prog)
+        .map(|entry| &entry.signature)
+}
+
+pub fn verify(prog: &[u8], signature: &[u8; 64]) -> bool {
+    PublicKey::new(PUBLIC_KEY)
+        .verify(prog, &Signature::new(*signature))
+        .is_ok()
+}
@@ -0,0 +1,13 @@
+//! Probe for an overflow of the stack of the thread running the benchmark.
+//! RIOT stores the address of the lowest word of every thread's stack in
+//! that word when it creates the thread (with DEVELHELP), an overflow
+//! overwrites it.
+
+/// Whether the canary at the bottom of the current thread's stack is intact.
+pub fn canary_intact() -> bool {
+    unsafe {
+        let thread = riot_sys::inline::thread_get_active();
+        let start = (*thread).stack_start as *const usize;
+        *start == start as usize
+    }
+}
@@ -0,0 +1,33 @@
+//! Flow control for hosts reading the rows live: after each row the
+//! benchmark waits for the host to acknowledge it with a byte on stdio, so
+//! that a congested link slows the benchmark down instead of losing rows.
+//! The wait is outside of the timed regions.
+
+use alloc::format;
+
+use super::config;
+use crate::output::ResultSink;
+use crate::util::time::now_us;
+
+/// Poll interval while no byte is available.
+const POLL_US: u32 = 1000;
+
+/// Waits for the acknowledgement of the row of `run`, any byte counts. After
+/// `STREAM_TIMEOUT_MS` a warning is reported and the benchmark continues.
+pub fn wait_ack(sink: &mut dyn ResultSink, run: usize) {
+    let timeout_us = config::stream_timeout_ms().saturating_mul(1000);
+    let start_us = now_us();
+    while unsafe { riot_sys::stdio_available() } <= 0 {
+        if now_us().wrapping_sub(start_us) >= timeout_us {
+            sink.comment(&format!(
+                "stream=warning no acknowledgement of run={} within {} ms, continuing",
+                run,
+                config::stream_timeout_ms()
+            ));
+            return;
+        }
+        unsafe { riot_sys::ztimer_sleep(riot_sys::ZTIMER_USEC, POLL_US) };
+    }
+    let mut byte = 0u8;
+    unsafe { riot_sys::stdio_read(&mut byte as *mut u8 as *mut _, 1) };
+}
@@ -0,0 +1,56 @@
+//! Programs embedded for the suite mode. The table is generated by build.rs
+//! from the `SUITE` environment variable, one entry per listed benchmark.
+
+use alloc::format;
+
+use super::config;
+use crate::output::ResultSink;
+
+/// A single program of the suite.
+pub struct Program {
+    /// Name of the benchmark, reported in the `program` column.
+    pub name: &'static str,
+    /// The program in the encoding expected by the active backend.
+    pub bytes: &'static [u8],
+}
+
+include!(concat!(env!("OUT_DIR"), "/suite.rs"));
+
+/// Reports the mean execution time of each program relative to the one of
+/// `BASELINE_PROGRAM`, as the speedup over it (above 1 is faster). `means`
+/// are the means of the programs of one VM kind, `None` for programs
+/// without runs.
+pub fn report_relative(sink: &mut dyn ResultSink, means: &[(&str, Option<u32>)]) {
+    let baseline = match config::baseline_program() {
+        Some(baseline) => baseline,
+        None => return,
+    };
+    let baseline_mean = match means.iter().find(|(name, _)| *name == baseline) {
+        Some((_, Some(mean))) => *mean,
+        Some((_, None)) => {
+            sink.comment(&format!("relative baseline={} has no runs, no ratios", baseline));
+            return;
+        }
+        None => {
+            sink.comment(&format!("relative baseline={} is not in SUITE, no ratios", baseline));
+            return;
+        }
+    };
+    for (name, mean) in means {
+        match mean {
+            Some(mean) => {
+                let speedup_centi = baseline_mean as u64 * 100 / (*mean).max(1) as u64;
+                sink.comment(&format!(
+                    "relative program={} baseline={} exec_mean_us={} baseline_mean_us={} speedup={}.{:02}x",
+                    name,
+                    baseline,
+                    mean,
+                    baseline_mean,
+                    speedup_centi / 100,
+                    speedup_centi % 100
+                ));
+            }
+            None => sink.comment(&format!("relative program={} baseline={} no runs", name, baseline)),
+        }
+    }
+}
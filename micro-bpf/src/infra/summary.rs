@@ -0,0 +1,314 @@
+//! Per-program summary reported after the rows, with the comparison
+//! against the embedded baseline.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+#[cfg(any(feature = "prometheus", feature = "dump-samples", feature = "criterion-csv"))]
+use alloc::vec::Vec;
+
+use crate::infra::baseline::{self, Percent};
+use crate::infra::config;
+#[cfg(feature = "error-summary")]
+use crate::infra::error::ErrorTally;
+use crate::output::ResultSink;
+#[cfg(feature = "scatter")]
+use crate::util::scatter::Scatter;
+#[cfg(feature = "sparkline")]
+use crate::util::sparkline::Sparkline;
+#[cfg(feature = "burst")]
+use crate::util::stats::{percentile, BurstTails};
+use crate::util::stats::{Accumulator, DriftDetector};
+#[cfg(feature = "confidence")]
+use crate::util::stats::Welford;
+#[cfg(feature = "top-slowest")]
+use crate::util::top::TopN;
+
+/// Statistics over the recorded iterations of a program.
+pub struct ProgramSummary {
+    pub name: String,
+    pub load_program_us: Accumulator,
+    pub execution_time_us: Accumulator,
+    pub correct: u32,
+    /// The program couldn't be run as configured.
+    pub failed: bool,
+    /// Memory safety was violated, no further programs may run.
+    pub aborted: bool,
+    /// Watches the execution times with `DRIFT_THRESHOLD_PCT`.
+    pub drift: Option<DriftDetector>,
+    /// Maximum execution time of each burst of `BURST_SIZE` iterations.
+    #[cfg(feature = "burst")]
+    pub bursts: BurstTails,
+    /// Mean and variance of the execution times, for `CONFIDENCE_PCT`.
+    #[cfg(feature = "confidence")]
+    pub confidence: Welford,
+    /// The `TOP_SLOWEST` iterations with the highest execution times.
+    #[cfg(feature = "top-slowest")]
+    pub slowest: TopN,
+    #[cfg(feature = "sparkline")]
+    pub sparkline: Sparkline,
+    #[cfg(feature = "scatter")]
+    pub scatter: Scatter,
+    /// Execution times for the quantiles of the Prometheus summary, the dump
+    /// of the samples and the criterion CSV.
+    #[cfg(any(feature = "prometheus", feature = "dump-samples", feature = "criterion-csv"))]
+    pub execution_samples: Vec<u32>,
+    #[cfg(feature = "dump-samples")]
+    pub load_samples: Vec<u32>,
+    /// First iteration after which the stack canary was overwritten.
+    #[cfg(feature = "canary-check")]
+    pub stack_violation: Option<usize>,
+    /// Delays from the trigger to the start of the execution.
+    #[cfg(feature = "trigger")]
+    pub wakeup_delay_us: Accumulator,
+    #[cfg(feature = "trigger")]
+    pub missed_deadlines: u32,
+    /// Errors of the recorded iterations.
+    #[cfg(feature = "error-summary")]
+    pub errors: ErrorTally,
+}
+
+impl ProgramSummary {
+    pub fn new(name: &str) -> Self {
+        ProgramSummary {
+            name: name.to_string(),
+            load_program_us: Accumulator::new(),
+            execution_time_us: Accumulator::new(),
+            correct: 0,
+            failed: false,
+            aborted: false,
+            drift: match config::drift_threshold_pct() {
+                0 => None,
+                threshold_pct => Some(DriftDetector::new(config::drift_window(), threshold_pct)),
+            },
+            #[cfg(feature = "burst")]
+            bursts: BurstTails::new(config::burst_size()),
+            #[cfg(feature = "confidence")]
+            confidence: Welford::new(),
+            #[cfg(feature = "top-slowest")]
+            slowest: TopN::new(config::top_slowest()),
+            #[cfg(feature = "sparkline")]
+            sparkline: Sparkline::new(config::iterations() * config::concurrent_vms()),
+            #[cfg(feature = "scatter")]
+            scatter: Scatter::new(config::iterations() * config::concurrent_vms()),
+            #[cfg(any(feature = "prometheus", feature = "dump-samples", feature = "criterion-csv"))]
+            execution_samples: Vec::with_capacity(config::iterations() * config::concurrent_vms()),
+            #[cfg(feature = "dump-samples")]
+            load_samples: Vec::with_capacity(config::iterations() * config::concurrent_vms()),
+            #[cfg(feature = "canary-check")]
+            stack_violation: None,
+            #[cfg(feature = "trigger")]
+            wakeup_delay_us: Accumulator::new(),
+            #[cfg(feature = "trigger")]
+            missed_deadlines: 0,
+            #[cfg(feature = "error-summary")]
+            errors: ErrorTally::new(),
+        }
+    }
+
+    pub fn add(&mut self, load_program_us: u32, execution_time_us: u32, correct: bool) {
+        #[cfg(feature = "sparkline")]
+        self.sparkline.add(self.execution_time_us.count() as usize, execution_time_us);
+        #[cfg(feature = "scatter")]
+        self.scatter.add(load_program_us, execution_time_us);
+        self.load_program_us.add(load_program_us);
+        self.execution_time_us.add(execution_time_us);
+        #[cfg(feature = "confidence")]
+        self.confidence.add(execution_time_us);
+        #[cfg(any(feature = "prometheus", feature = "dump-samples", feature = "criterion-csv"))]
+        self.execution_samples.push(execution_time_us);
+        #[cfg(feature = "dump-samples")]
+        self.load_samples.push(load_program_us);
+        self.correct += correct as u32;
+    }
+
+    pub fn fail(&mut self) {
+        self.failed = true;
+    }
+
+    pub fn abort(&mut self) {
+        self.failed = true;
+        self.aborted = true;
+    }
+
+    pub fn all_correct(&self) -> bool {
+        self.correct == self.execution_time_us.count()
+    }
+
+    /// Reports the summary and the baseline comparison. Returns whether the
+    /// program passed, i.e. it ran, all iterations were correct and, with
+    /// `REGRESSION_GATE=1`, no metric regressed.
+    pub fn report(&self, sink: &mut dyn ResultSink) -> bool {
+        sink.comment(&format!(
+            "summary program={} runs={} correct={} load_mean_us={} exec_mean_us={} exec_min_us={} exec_max_us={}",
+            self.name,
+            self.execution_time_us.count(),
+            self.correct,
+            self.load_program_us.mean().unwrap_or(0),
+            self.execution_time_us.mean().unwrap_or(0),
+            self.execution_time_us.min().unwrap_or(0),
+            self.execution_time_us.max().unwrap_or(0)
+        ));
+        sink.summary(self);
+
+        #[cfg(feature = "sparkline")]
+        if let Some(plot) = self.sparkline.render() {
+            sink.comment(&format!(
+                "sparkline program={} exec_us={}..{} {}",
+                self.name,
+                self.execution_time_us.min().unwrap_or(0),
+                self.execution_time_us.max().unwrap_or(0),
+                plot
+            ));
+        }
+
+        #[cfg(feature = "trigger")]
+        sink.comment(&format!(
+            "trigger program={} period_ms={} missed_deadlines={} wakeup_mean_us={} wakeup_max_us={}",
+            self.name,
+            config::trigger_period_ms(),
+            self.missed_deadlines,
+            self.wakeup_delay_us.mean().unwrap_or(0),
+            self.wakeup_delay_us.max().unwrap_or(0)
+        ));
+
+        if let Some(drift) = &self.drift {
+            match drift.baseline_us() {
+                Some(baseline_us) => sink.comment(&format!(
+                    "drift program={} baseline_us={} threshold_pct={} drifted_runs={}",
+                    self.name,
+                    baseline_us,
+                    config::drift_threshold_pct(),
+                    drift.drifted()
+                )),
+                None => sink.comment(&format!(
+                    "drift program={} fewer runs than DRIFT_WINDOW={}, no baseline",
+                    self.name,
+                    config::drift_window()
+                )),
+            }
+        }
+
+        #[cfg(feature = "burst")]
+        {
+            let mut tails = self.bursts.tails().to_vec();
+            sink.comment(&format!(
+                "burst program={} size={} bursts={} incomplete_runs={} tail_p50_us={} tail_p95_us={} tail_max_us={}",
+                self.name,
+                config::burst_size(),
+                tails.len(),
+                self.bursts.pending(),
+                percentile(&mut tails, 50).unwrap_or(0),
+                percentile(&mut tails, 95).unwrap_or(0),
+                tails.last().copied().unwrap_or(0)
+            ));
+        }
+
+        #[cfg(feature = "confidence")]
+        match self.confidence.half_width_ns() {
+            Some(half_width_ns) => {
+                let centi_pct = self.confidence.half_width_centi_pct().unwrap_or(0);
+                sink.comment(&format!(
+                    "confidence program={} runs={} target_pct={} ci95_half_width_ns={} ci95_pct={}.{:02} reached={}",
+                    self.name,
+                    self.confidence.count(),
+                    config::confidence_pct(),
+                    half_width_ns,
+                    centi_pct / 100,
+                    centi_pct % 100,
+                    self.confidence.within(config::confidence_pct())
+                ));
+            }
+            None => sink.comment(&format!(
+                "confidence program={} runs={} fewer than {} runs, no interval reached=false",
+                self.name,
+                self.confidence.count(),
+                crate::util::stats::CONFIDENCE_MIN_SAMPLES
+            )),
+        }
+
+        #[cfg(feature = "top-slowest")]
+        for (rank, (run, execution_time_us)) in self.slowest.sorted().into_iter().enumerate() {
+            sink.comment(&format!(
+                "slowest program={} rank={} run={} execution_time_us={}",
+                self.name,
+                rank + 1,
+                run,
+                execution_time_us
+            ));
+        }
+
+        #[cfg(feature = "scatter")]
+        if let Some(plot) = self.scatter.render() {
+            sink.comment(&format!(
+                "scatter program={} samples={} x=load_us {}..{} y=exec_us {}..{} (top row is the slowest)",
+                self.name, plot.samples, plot.x.min, plot.x.max, plot.y.min, plot.y.max
+            ));
+            for row in plot.rows.iter() {
+                sink.comment(&format!("scatter |{}|", row));
+            }
+        }
+
+        #[cfg(feature = "dump-samples")]
+        crate::infra::samples::dump(sink, &self.name, &self.load_samples, &self.execution_samples);
+
+        let regressed = match baseline::lookup(&self.name) {
+            Some(entry) => {
+                let load = self.compare(sink, "load_program_us", self.load_program_us, entry.load_program_us);
+                let exec = self.compare(sink, "execution_time_us", self.execution_time_us, entry.execution_time_us);
+                load || exec
+            }
+            None => {
+                sink.comment(&format!("baseline program={} no baseline", self.name));
+                false
+            }
+        };
+
+        !self.failed && self.all_correct() && !(regressed && config::regression_gate())
+    }
+
+    /// Reports the comparison of one metric, returns whether it regressed.
+    fn compare(&self, sink: &mut dyn ResultSink, metric: &str, current: Accumulator, baseline_mean: u32) -> bool {
+        let current_mean = match current.mean() {
+            Some(mean) => mean,
+            None => return false,
+        };
+        match baseline::change_centi_pct(current_mean, baseline_mean) {
+            Some(change) => {
+                let regression = baseline::is_regression(change, config::regression_threshold_pct());
+                sink.comment(&format!(
+                    "baseline program={} metric={} current_us={} baseline_us={} change={}{}",
+                    self.name,
+                    metric,
+                    current_mean,
+                    baseline_mean,
+                    Percent(change),
+                    if regression { " REGRESSION" } else { "" }
+                ));
+                regression
+            }
+            None => {
+                sink.comment(&format!(
+                    "baseline program={} metric={} current_us={} baseline_us=0 change=n/a",
+                    self.name, metric, current_mean
+                ));
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::TextSink;
+
+    #[test]
+    fn program_without_baseline_is_no_regression() {
+        let mut summary = ProgramSummary::new("not_in_the_baseline");
+        summary.add(10, 1_000_000, true);
+        let mut sink = TextSink(String::new());
+        assert!(summary.report(&mut sink));
+        assert!(sink.0.contains("# baseline program=not_in_the_baseline no baseline\n"));
+        assert!(!sink.0.contains("REGRESSION"));
+    }
+}
@@ -0,0 +1,30 @@
+//! Synchronisation between the benchmark threads.
+
+use portable_atomic::{AtomicUsize, Ordering};
+
+/// Blocks threads until `count` of them are waiting, so that they start
+/// their work at the same time. Single use.
+pub struct Barrier {
+    count: usize,
+    arrived: AtomicUsize,
+}
+
+impl Barrier {
+    pub const fn new(count: usize) -> Self {
+        Barrier {
+            count,
+            arrived: AtomicUsize::new(0),
+        }
+    }
+
+    /// Waits for all threads, returns true in exactly one of them (the last
+    /// one to arrive).
+    pub fn wait(&self) -> bool {
+        let last = self.arrived.fetch_add(1, Ordering::AcqRel) + 1 == self.count;
+        while self.arrived.load(Ordering::Acquire) < self.count {
+            // Let the threads which still have to arrive run
+            unsafe { riot_sys::thread_yield() };
+        }
+        last
+    }
+}
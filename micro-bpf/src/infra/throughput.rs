@@ -0,0 +1,88 @@
+//! Control-plane workload: small programs are loaded, verified and dropped
+//! again back to back, without being executed, as for a host which streams
+//! many of them. This stresses the allocator and the verifier. The loads are
+//! not reported one by one, only their rate and distribution per program.
+
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use riot_wrappers::ztimer::Clock;
+
+use super::config;
+use super::load::load_program;
+use crate::middleware::analysis::{referenced_helpers, ProgramFormat};
+use crate::output::ResultSink;
+use crate::util::stats::{percentile, Accumulator};
+use crate::util::time::{now_us, Elapsed};
+
+/// Loads `LOAD_THROUGHPUT_COUNT` programs, cycling through `programs`
+/// (name, bytes), and reports the programs loaded per second.
+pub fn run(sink: &mut dyn ResultSink, programs: &[(&str, &[u8])]) {
+    let micro_sec = Clock::usec();
+    let count = config::load_throughput_count();
+    let kind = super::vm::current();
+    let per_program = count / programs.len() + 1;
+    let mut samples: Vec<Vec<u32>> = programs.iter().map(|_| Vec::with_capacity(per_program)).collect();
+    let mut failed = vec![0u32; programs.len()];
+    // Found once per program like for the iterations, a program calling a
+    // helper which isn't implemented fails every load
+    let helpers: Vec<_> = programs
+        .iter()
+        .map(|(_, bytes)| referenced_helpers(bytes, ProgramFormat::FemtoContainersHeader).ok())
+        .collect();
+
+    let start_us = now_us();
+    for i in 0..count {
+        let index = i % programs.len();
+        let helpers = match &helpers[index] {
+            Some(helpers) => helpers,
+            None => {
+                failed[index] += 1;
+                continue;
+            }
+        };
+        let mut loaded = false;
+        let duration = micro_sec
+            .time(|| {
+                // The VM is dropped within the measurement, freeing is part
+                // of the cycle
+                loaded = matches!(
+                    load_program(programs[index].1, rbpf::InterpreterVariant::FemtoContainersHeader, kind, helpers, true),
+                    Ok(program) if program.verified
+                );
+            })
+            .expect("failed to measure load time")
+            .us();
+        if loaded {
+            samples[index].push(duration);
+        } else {
+            failed[index] += 1;
+        }
+    }
+    let wall_us = now_us().wrapping_sub(start_us);
+
+    for (((name, _), times), failed) in programs.iter().zip(samples.iter_mut()).zip(failed.iter()) {
+        let mut load_us = Accumulator::new();
+        for time in times.iter() {
+            load_us.add(*time);
+        }
+        sink.comment(&format!(
+            "load_throughput program={} loads={} failed={} load_mean_us={} load_p50_us={} load_p95_us={} load_max_us={}",
+            name,
+            load_us.count(),
+            failed,
+            load_us.mean().unwrap_or(0),
+            percentile(times, 50).unwrap_or(0),
+            percentile(times, 95).unwrap_or(0),
+            load_us.max().unwrap_or(0)
+        ));
+    }
+    let loaded: usize = samples.iter().map(Vec::len).sum();
+    sink.comment(&format!(
+        "load_throughput programs={} loads={} wall_us={} programs_per_s={}",
+        programs.len(),
+        loaded,
+        wall_us,
+        loaded as u64 * 1_000_000 / wall_us.max(1) as u64
+    ));
+}
@@ -0,0 +1,47 @@
+//! Checks the timing harness before the benchmark: a sleep of
+//! `TIMER_SELFTEST_US` on ZTIMER_USEC is measured the way the iterations are,
+//! with a timed closure converted by `Elapsed::us`. A clock configured with
+//! the wrong frequency, ticks taken as the wrong unit or a sleep that returns
+//! early puts the measurement outside the tolerance. The sleep never ends
+//! before its duration, only the late side has the tolerance.
+
+use alloc::format;
+use riot_wrappers::ztimer::{Clock, Ticks};
+
+use super::config;
+use crate::output::ResultSink;
+use crate::util::time::Elapsed;
+
+/// Measures the sleep and reports it, false if the timer can't be trusted.
+pub fn run(sink: &mut dyn ResultSink) -> bool {
+    let micro_sec = Clock::usec();
+    let expected_us = config::timer_selftest_us();
+    let tolerance_us = (expected_us as u64 * config::timer_selftest_tolerance_pct() as u64 / 100) as u32;
+    let sleep = Ticks::from_duration(core::time::Duration::from_micros(expected_us as u64))
+        .expect("TIMER_SELFTEST_US overflows the microsecond timer");
+    let measured_us = micro_sec
+        .time(|| micro_sec.sleep(sleep))
+        .expect("failed to measure the self-test sleep")
+        .us();
+
+    let passed = measured_us >= expected_us && measured_us - expected_us <= tolerance_us;
+    // Spaces instead of `;`, a line with `;` would be parsed as a row
+    sink.metadata(
+        "timer_selftest",
+        &format!(
+            "{} measured_us={} expected_us={} tolerance_us={}",
+            if passed { "pass" } else { "fail" },
+            measured_us,
+            expected_us,
+            tolerance_us
+        ),
+    );
+    if !passed {
+        sink.banner("TIMER SELF-TEST FAILED");
+        sink.comment(&format!(
+            "a sleep of {} us measured {} us, check the ztimer configuration of the board",
+            expected_us, measured_us
+        ));
+    }
+    passed
+}
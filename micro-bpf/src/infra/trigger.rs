@@ -0,0 +1,100 @@
+//! Periodic trigger of the event-driven mode (`TRIGGER_PERIOD_MS`).
+//!
+//! A ztimer_periodic callback posts an event to a queue owned by the
+//! benchmark thread, which blocks on the queue and runs one iteration per
+//! event, like a program attached to a sensor timer. The callback stores when
+//! it fired, so that the benchmark can measure the delay until the execution
+//! started.
+
+use core::mem::MaybeUninit;
+use core::ptr::addr_of_mut;
+use portable_atomic::{AtomicBool, AtomicU32, Ordering};
+
+use crate::infra::config;
+use crate::util::time::now_us;
+
+static mut QUEUE: MaybeUninit<riot_sys::inline::event_queue_t> = MaybeUninit::uninit();
+static mut EVENT: MaybeUninit<riot_sys::event_t> = MaybeUninit::uninit();
+static mut TIMER: MaybeUninit<riot_sys::ztimer_periodic_t> = MaybeUninit::uninit();
+/// Time of the oldest trigger that wasn't served yet.
+static FIRED_AT_US: AtomicU32 = AtomicU32::new(0);
+/// Set when the trigger fires, cleared once its iteration is done.
+static PENDING: AtomicBool = AtomicBool::new(false);
+static MISSED: AtomicU32 = AtomicU32::new(0);
+
+fn queue() -> *mut riot_sys::inline::event_queue_t {
+    unsafe { addr_of_mut!(QUEUE).cast() }
+}
+
+fn event() -> *mut riot_sys::event_t {
+    unsafe { addr_of_mut!(EVENT).cast() }
+}
+
+fn timer() -> *mut riot_sys::ztimer_periodic_t {
+    unsafe { addr_of_mut!(TIMER).cast() }
+}
+
+/// Starts the trigger. Must be called on the thread that waits for it, the
+/// event queue belongs to the calling thread.
+pub fn start() {
+    PENDING.store(false, Ordering::Relaxed);
+    MISSED.store(0, Ordering::Relaxed);
+    unsafe {
+        riot_sys::inline::event_queue_init(queue());
+        event().write(riot_sys::event_t {
+            list_node: riot_sys::clist_node_t {
+                next: core::ptr::null_mut(),
+            },
+            handler: Some(handler),
+        });
+        riot_sys::ztimer_periodic_init(
+            riot_sys::ZTIMER_USEC,
+            timer(),
+            Some(fire),
+            core::ptr::null_mut(),
+            config::trigger_period_ms() * 1000,
+        );
+        riot_sys::ztimer_periodic_start(timer());
+    }
+}
+
+/// Blocks until the trigger fires, returns the time it fired at.
+pub fn wait() -> u32 {
+    unsafe {
+        riot_sys::inline::event_wait(queue());
+    }
+    FIRED_AT_US.load(Ordering::Acquire)
+}
+
+/// Marks the iteration of the last trigger as done, triggers after this are
+/// no longer missed.
+pub fn done() {
+    PENDING.store(false, Ordering::Release);
+}
+
+/// Stops the trigger, returns the number of triggers that fired while the
+/// previous one was still being served.
+pub fn stop() -> u32 {
+    unsafe {
+        riot_sys::ztimer_periodic_stop(timer());
+        riot_sys::event_cancel(queue() as *mut riot_sys::event_queue_t, event());
+    }
+    MISSED.load(Ordering::Acquire)
+}
+
+/// Runs in the interrupt of the timer. A missed trigger doesn't move the
+/// fire time, the delay of the late iteration counts from the first trigger
+/// it serves.
+unsafe extern "C" fn fire(_arg: *mut core::ffi::c_void) -> bool {
+    if PENDING.swap(true, Ordering::AcqRel) {
+        MISSED.fetch_add(1, Ordering::Relaxed);
+    } else {
+        FIRED_AT_US.store(now_us(), Ordering::Release);
+        riot_sys::event_post(queue() as *mut riot_sys::event_queue_t, event());
+    }
+    // Keep the timer running
+    true
+}
+
+/// The event is taken with event_wait and never dispatched.
+unsafe extern "C" fn handler(_event: *mut riot_sys::event_t) {}
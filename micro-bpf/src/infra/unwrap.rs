@@ -0,0 +1,41 @@
+//! The validation a deployment does on a program that arrives wrapped, before
+//! it is handed to the VM. There is no SUIT parser for rbpf, this stands in
+//! for one: the Femto-Container header is checked (magic, sections within
+//! the program) and the payload is compared to the size and digest a
+//! manifest would carry. The manifest is recorded when the program starts,
+//! validating it is timed per iteration as unwrap_us.
+
+use portable_atomic::{AtomicU32, AtomicUsize, Ordering};
+
+use crate::middleware::analysis::{header_text, FC_HEADER_SIZE, FC_MAGIC};
+use crate::util::checksum::fnv1a;
+
+const INSN_SIZE: usize = 8;
+
+/// Manifest of the program under benchmark: payload length and digest.
+static PAYLOAD_LEN: AtomicUsize = AtomicUsize::new(0);
+static PAYLOAD_DIGEST: AtomicU32 = AtomicU32::new(0);
+
+fn payload(prog: &[u8]) -> &[u8] {
+    prog.get(FC_HEADER_SIZE..).unwrap_or(&[])
+}
+
+/// Records the manifest of `prog`, as the sender would have built it.
+pub fn record(prog: &[u8]) {
+    let payload = payload(prog);
+    PAYLOAD_LEN.store(payload.len(), Ordering::Relaxed);
+    PAYLOAD_DIGEST.store(fnv1a(payload), Ordering::Relaxed);
+}
+
+/// Whether `prog` is a well formed Femto-Container matching the recorded
+/// manifest.
+pub fn validate(prog: &[u8]) -> bool {
+    // The function table follows the text
+    let header_ok = prog.starts_with(FC_MAGIC)
+        && header_text(prog).is_some_and(|text| text.len() % INSN_SIZE == 0);
+    if !header_ok {
+        return false;
+    }
+    let payload = payload(prog);
+    payload.len() == PAYLOAD_LEN.load(Ordering::Relaxed) && fnv1a(payload) == PAYLOAD_DIGEST.load(Ordering::Relaxed)
+}
@@ -0,0 +1,113 @@
+//! The rbpf VM types the interpreter runs the program on, selected with
+//! `VM_KIND`. They differ in the memory handed to the program: the mbuff VM
+//! gets the memory and an empty metadata buffer, the raw VM only the memory
+//! and the nodata VM nothing.
+
+use alloc::vec::Vec;
+use portable_atomic::{AtomicU8, Ordering};
+use rbpf::{EbpfVmMbuff, EbpfVmNoData, EbpfVmRaw, Error, InterpreterVariant};
+
+use crate::middleware::helpers::{AcceptingHelpers, HelperFunction};
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum VmKind {
+    Mbuff,
+    Raw,
+    NoData,
+}
+
+impl VmKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "mbuff" => Some(VmKind::Mbuff),
+            "raw" => Some(VmKind::Raw),
+            "nodata" => Some(VmKind::NoData),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VmKind::Mbuff => "mbuff",
+            VmKind::Raw => "raw",
+            VmKind::NoData => "nodata",
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(VmKind::Mbuff as u8);
+
+/// Selects the kind of the VMs created from now on, also by the threads of
+/// concurrent VMs.
+pub fn set(kind: VmKind) {
+    CURRENT.store(kind as u8, Ordering::Relaxed);
+}
+
+pub fn current() -> VmKind {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => VmKind::Raw,
+        2 => VmKind::NoData,
+        _ => VmKind::Mbuff,
+    }
+}
+
+pub enum Vm<'a> {
+    Mbuff(EbpfVmMbuff<'a>),
+    Raw(EbpfVmRaw<'a>),
+    NoData(EbpfVmNoData<'a>),
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(kind: VmKind, prog: &'a [u8], variant: InterpreterVariant) -> Result<Self, Error> {
+        Ok(match kind {
+            VmKind::Mbuff => Vm::Mbuff(EbpfVmMbuff::new(Some(prog), variant)?),
+            VmKind::Raw => Vm::Raw(EbpfVmRaw::new(Some(prog), variant)?),
+            VmKind::NoData => Vm::NoData(EbpfVmNoData::new(Some(prog), variant)?),
+        })
+    }
+
+    /// Registers a helper which has no `HelperFunctionID`.
+    pub fn register_raw_helper(&mut self, id: u32, function: fn(u64, u64, u64, u64, u64) -> u64) {
+        match self {
+            Vm::Mbuff(vm) => {
+                let _ = vm.register_helper(id, function);
+            }
+            Vm::Raw(vm) => {
+                let _ = vm.register_helper(id, function);
+            }
+            Vm::NoData(vm) => {
+                let _ = vm.register_helper(id, function);
+            }
+        }
+    }
+
+    /// Returns whether the program passes the verifier.
+    pub fn verify_loaded_program(&self) -> bool {
+        match self {
+            Vm::Mbuff(vm) => vm.verify_loaded_program().is_ok(),
+            Vm::Raw(vm) => vm.verify_loaded_program().is_ok(),
+            Vm::NoData(vm) => vm.verify_loaded_program().is_ok(),
+        }
+    }
+
+    /// Executes the program, the nodata VM ignores `mem`. The program may
+    /// write to its memory with any VM, the raw VM only expresses it in its
+    /// signature.
+    pub fn execute_program(&self, mem: &mut [u8], allowed_memory_regions: Vec<(u64, u64)>) -> Result<u64, Error> {
+        match self {
+            Vm::Mbuff(vm) => vm.execute_program(mem, &[], allowed_memory_regions),
+            Vm::Raw(vm) => vm.execute_program(mem, allowed_memory_regions),
+            Vm::NoData(vm) => vm.execute_program(allowed_memory_regions),
+        }
+    }
+}
+
+impl AcceptingHelpers for Vm<'_> {
+    fn register_helper(&mut self, helper: HelperFunction) {
+        match self {
+            Vm::Mbuff(vm) => AcceptingHelpers::register_helper(vm, helper),
+            Vm::Raw(vm) => AcceptingHelpers::register_helper(vm, helper),
+            Vm::NoData(vm) => AcceptingHelpers::register_helper(vm, helper),
+        }
+    }
+}
@@ -1,12 +1,16 @@
 // SPDX-FileCopyrightText: 2020 Christian Amsüss
 // SPDX-License-Identifier: LGPL-2.1-only
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
 mod infra;
 mod middleware;
+mod output;
 mod util;
 
-use alloc::string::ToString;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+#[cfg(any(feature = "header-cost", all(feature = "compare-mem", feature = "jit")))]
 use rbpf::EbpfVmMbuff;
 use riot_wrappers::ztimer::{Clock, Ticks};
 use riot_wrappers::{println, riot_main};
@@ -18,9 +22,22 @@ extern crate rust_riotmodules;
 use alloc::vec::Vec;
 use alloc::vec;
 use alloc::collections::BTreeMap;
-use alloc::boxed::Box;
+use portable_atomic::{AtomicU32, AtomicUsize, Ordering};
 
-use crate::middleware::helpers::register_all;
+use crate::infra::allocator;
+use crate::infra::config::{self, Config, Warmup};
+use crate::infra::error::BenchError;
+use crate::infra::summary::ProgramSummary;
+use crate::infra::vm::Vm;
+use crate::middleware::analysis::referenced_helpers;
+use crate::middleware::analysis::{referenced_helper_ids, ProgramFormat};
+#[cfg(any(feature = "header-cost", all(feature = "compare-mem", feature = "jit")))]
+use crate::middleware::helpers::register_helpers;
+use crate::middleware::helpers::HelperFunction;
+use crate::output::{ResultSink, RowData, StdioSink, Value};
+use crate::util::guarded::{Damage, Guarded};
+use crate::util::time::Elapsed;
+use crate::util::stats::{Accumulator, Run, StabilityDetector};
 
 #[macro_export]
 macro_rules! print {
@@ -34,6 +51,7 @@ macro_rules! print {
 riot_main!(main);
 
 #[cfg(feature = "libud")]
+#[derive(Clone)]
 #[repr(C)]
 struct Context {
     a: [[i64; 20]; 20],
@@ -42,7 +60,86 @@ struct Context {
     y: [i64; 100],
 }
 
-const ITERATIONS_STR: &str = env!("ITERATIONS");
+#[cfg(feature = "libud")]
+impl Context {
+    /// The input of every execution, also of the native reference.
+    fn new() -> Self {
+        Context {
+            a: [[0; 20]; 20],
+            b: [0; 20],
+            x: [0; 20],
+            y: [0; 100],
+        }
+    }
+}
+
+#[cfg(all(feature = "register-overhead", feature = "jit"))]
+compile_error!("register-overhead registers the helpers into interpreter VMs, the JIT takes them at compile time");
+
+#[cfg(all(feature = "verify-reject", feature = "jit"))]
+compile_error!("verify-reject times the interpreter's verifier and cannot be combined with jit");
+
+#[cfg(all(feature = "seeds", not(feature = "libud")))]
+compile_error!("seeds fill the Context of BENCHMARK=libud, the other workloads have no input");
+
+#[cfg(all(feature = "seeds", feature = "confidence"))]
+compile_error!("seeds run one iteration per seed, confidence runs until the interval is narrow enough");
+
+#[cfg(all(feature = "unwrap", feature = "jit"))]
+compile_error!("unwrap validates the Femto-Container header, the JIT loads the raw object file");
+
+#[cfg(all(feature = "jit-timeout", not(feature = "jit")))]
+compile_error!("jit-timeout bounds the JIT compilation, enable jit");
+
+#[cfg(all(feature = "verify-registration", feature = "jit"))]
+compile_error!("verify-registration times the interpreter's verifier and cannot be combined with jit");
+
+#[cfg(all(feature = "confidence", feature = "trigger"))]
+compile_error!("confidence decides after each iteration whether to go on, the trigger runs a fixed number of them");
+
+#[cfg(all(feature = "dump-samples", any(feature = "reserve-heap", feature = "fragment-heap")))]
+compile_error!("dump-samples buffers every sample and cannot be combined with reserve-heap or fragment-heap, which leave little heap");
+
+#[cfg(all(feature = "prewarm-mbuff", feature = "jit"))]
+compile_error!("prewarm-mbuff reads the mbuff of the interpreter, the JIT is executed without one");
+
+#[cfg(all(feature = "load-throughput", feature = "jit"))]
+compile_error!("load-throughput times the interpreter's load and verification and cannot be combined with jit");
+
+#[cfg(all(feature = "stop-at-insn", feature = "jit"))]
+compile_error!("stop-at-insn patches the program of the interpreter, the JIT compiles the raw object file");
+
+#[cfg(all(feature = "align-prog", feature = "jit"))]
+compile_error!("align-prog aligns the program of the interpreter, the JIT copies the program itself");
+
+#[cfg(all(feature = "dump-context", not(feature = "libud")))]
+compile_error!("dump-context prints the Context of BENCHMARK=libud, the other workloads have none");
+
+#[cfg(all(feature = "reference", not(feature = "libud")))]
+compile_error!("reference has a native implementation of BENCHMARK=libud only, the other workloads check their result themselves");
+
+#[cfg(all(feature = "compare-mem", not(any(feature = "header-cost", feature = "jit"))))]
+compile_error!("compare-mem compares the memory of the JIT and the interpreter or of the two header-cost variants, enable jit or header-cost");
+
+#[cfg(all(feature = "compare-mem", feature = "exec-batch"))]
+compile_error!("compare-mem executes the second variant once and cannot be combined with exec-batch");
+
+#[cfg(all(feature = "header-cost", feature = "jit"))]
+compile_error!("header-cost compares interpreter load formats and cannot be combined with jit");
+
+/// The program under benchmark. Its location is resolved and validated by
+/// build.rs (see `BENCH_BIN_PATH` / `BENCH_OBJ_PATH`). In suite mode the
+/// programs are taken from `infra::suite::PROGRAMS` instead.
+#[cfg(all(not(feature = "suite"), not(feature = "base64-load"), not(feature = "jit")))]
+const PROG: &[u8] = include_bytes!(env!("BENCH_BIN_PATH"));
+#[cfg(all(not(feature = "suite"), not(feature = "base64-load"), feature = "jit"))]
+const PROG: &[u8] = include_bytes!(env!("BENCH_OBJ_PATH"));
+
+/// The same program as `benchmark.bin`, but as the raw ELF object file. Used
+/// to load the program a second time without the Femto-Containers header so
+/// that the cost of handling the header can be isolated.
+#[cfg(feature = "header-cost")]
+const RAW_PROG: &[u8] = include_bytes!(env!("BENCH_OBJ_PATH"));
 
 #[cfg(feature = "jit")]
 #[repr(C, align(4))]
@@ -51,130 +148,1882 @@ struct AlignedBuffer([u8; JIT_MEMORY_BUFF_SIZE]);
 #[cfg(feature = "jit")]
 const JIT_MEMORY_BUFF_SIZE: usize = 20 * 1024;
 
-fn main() {
-    let prog: &[u8] = if cfg!(feature = "jit") {
-        include_bytes!("../benchmark.o")
-    } else {
-        include_bytes!("../benchmark.bin")
-    };
+#[cfg(all(feature = "helper-pattern", feature = "jit"))]
+compile_error!("helper-pattern relies on the interpreter's verifier and cannot be combined with jit");
+
+#[cfg(all(feature = "reverify", feature = "jit"))]
+compile_error!("reverify verifies the interpreter VM again and cannot be combined with jit");
+
+#[cfg(all(feature = "suite", feature = "header-cost"))]
+compile_error!("header-cost only supports a single program, disable suite");
+
+#[cfg(all(feature = "base64-load", any(feature = "suite", feature = "header-cost")))]
+compile_error!("base64-load receives a single program at runtime, disable suite and header-cost");
+
+#[cfg(all(feature = "vfs-helpers", feature = "jit"))]
+compile_error!("vfs-helpers passes the read buffer through the mbuff, which the JIT doesn't get");
+
+#[cfg(all(feature = "verify-profile", feature = "jit"))]
+compile_error!("verify-profile times the interpreter's verifier and cannot be combined with jit");
+
+#[cfg(all(feature = "base64-load", feature = "signed-programs"))]
+compile_error!("signed-programs checks signatures embedded at build time and cannot be combined with base64-load");
+
+/// How the benchmarked program is handed to rbpf, which takes different code
+/// paths for both. rbpf itself is built without default features.
+const RBPF_FLAGS: &str = if cfg!(feature = "jit") {
+    "no-default-features,jit,raw-object-file"
+} else {
+    "no-default-features,interpreter,femto-containers-header"
+};
 
+/// Name of the execution backend, reported in the `variant` column.
+#[cfg(any(feature = "suite", feature = "prometheus"))]
+const VARIANT: &str = if cfg!(feature = "jit") { "jit" } else { "interpreter" };
+
+fn main() {
     let micro_sec = Clock::usec();
-    let iterations: usize = ITERATIONS_STR.parse().expect("Failed to parse ITERATIONS");
+    let iterations = config::iterations();
 
     // Sleep a bit to wait for the serial to be ready
     micro_sec.sleep(Ticks::from_duration(core::time::Duration::from_secs(3)).
             expect("5 would only overflow a nanosecond timer"));
+    let settled_us = util::time::now_us();
 
-    println!("=== Benchmark Begins ===");
-    println!("iteration;init_runtime_us;load_program_us;execution_time_us;correct");
+    #[cfg(not(feature = "dual-uart"))]
+    let console = StdioSink;
+    #[cfg(feature = "dual-uart")]
+    let console = output::DualUartSink::new(config::data_uart(), config::data_uart_baud());
+    #[cfg(feature = "dual-uart")]
+    let interfaces = console.describe();
+    // The CBOR records keep the absolute times
+    #[cfg(feature = "delta-encode")]
+    let console = output::DeltaSink::new(console);
+    #[cfg(not(feature = "cbor-results"))]
+    let output = console;
+    // The CSV stays on the console, the records follow each line
+    #[cfg(feature = "cbor-results")]
+    let output = output::Tee(console, output::CborSink::new(StdioSink));
+    // First, so that the report is sent before the end banner
+    #[cfg(not(feature = "coap-report"))]
+    let mut output = output;
+    #[cfg(feature = "coap-report")]
+    let mut output = output::Tee(output::CoapReportSink::new(config::coap_report_uri()), output);
+    let sink: &mut dyn ResultSink = &mut output;
 
-    for i in 0..iterations {
-        print!("{};", i);
-        print!("0;"); // init runtime not applicable here
+    sink.banner("Benchmark Begins");
+    #[cfg(feature = "dual-uart")]
+    sink.metadata("output", &interfaces);
+    sink.metadata("rbpf_version", env!("RBPF_VERSION"));
+    sink.metadata("rbpf_flags", RBPF_FLAGS);
+    // The verifier's only limit, rbpf takes no parameters for the
+    // verification
+    sink.metadata("verifier_max_insns", &format!("{} (fixed by rbpf)", rbpf::ebpf::PROG_MAX_INSNS));
+    // The VMs allocate the program's stack themselves, its size can't be
+    // passed on construction
+    sink.metadata("ebpf_stack_size", &format!("{} (fixed by rbpf)", rbpf::ebpf::STACK_SIZE));
+    // Frequency of the clock behind the timed closures, the rows are
+    // converted from its ticks
+    let clock_hz = util::time::clock_hz(&micro_sec);
+    let tick_ps = util::time::tick_ps(clock_hz);
+    sink.metadata(
+        "clock",
+        &format!(
+            "ztimer_usec hz={} (compile-time frequency of the clock type) ns_per_tick={}.{:03}",
+            clock_hz,
+            tick_ps / 1000,
+            tick_ps % 1000
+        ),
+    );
+    #[cfg(feature = "mbuff-guard")]
+    sink.metadata("mbuff_guard_bytes", &format!("{}", util::guarded::GUARD_BYTES));
+    infra::footprint::report(sink);
+    #[cfg(feature = "encodings")]
+    infra::encodings::report(sink);
+    #[cfg(feature = "deterministic-helpers")]
+    sink.metadata("deterministic_helpers", middleware::deterministic::AFFECTED);
+    #[cfg(feature = "reference")]
+    sink.metadata("reference", &format!("expected={}", infra::reference::compute()));
+    // After the reference, which gives the expected result. Leaked, the
+    // concurrent workers hold it until the end of the run
+    let config: &'static Config = Box::leak(Box::new(Config::parse()));
+    #[cfg(feature = "seeds")]
+    sink.metadata(
+        "seeds",
+        &config.seeds.iter().map(|seed| format!("{}", seed)).collect::<Vec<_>>().join(","),
+    );
+    match config.success_mode {
+        config::SuccessMode::Expected(expected) => {
+            sink.metadata("success_mode", &format!("expected result={}", expected))
+        }
+        mode => sink.metadata("success_mode", mode.name()),
+    }
+    sink.metadata("reduce", config.reduce.name());
+    sink.metadata("arg_endian", config.arg_endian.name());
+    if !config.run_label.is_empty() {
+        sink.metadata("label", config.run_label);
+    }
 
-        let mut vm: Option<EbpfVmMbuff> = None;
+    let mut passed = true;
 
-        #[cfg(feature = "jit")]
-        let mut jitted_fn: Option<unsafe fn(*mut u8, usize, *mut u8, usize) -> u32> = None;
+    #[cfg(feature = "ns-precision")]
+    match util::time::ns::timer_hz() {
+        Some(hz) => sink.comment(&format!("ns_precision=available timer_hz={}", hz)),
+        None => sink.comment("ns_precision=unavailable, the timer doesn't resolve below a microsecond"),
+    }
 
-        #[cfg(feature = "libud")]
-        let ctx = Context {
-            a: [[0; 20]; 20], // Initialize as needed
-            b: [0; 20],
-            x: [0; 20],
-            y: [0; 100],
+    // Before the helper self-test, whose timings rely on the timer
+    #[cfg(feature = "timer-selftest")]
+    if !infra::timer_selftest::run(sink) {
+        sink.comment("refusing to run the benchmark, set TIMER_SELFTEST=0 to run it anyway");
+        sink.metadata("verdict", "FAIL");
+        sink.finish();
+        return;
+    }
+
+    #[cfg(feature = "selftest")]
+    if !middleware::selftest::run(sink) {
+        sink.comment("refusing to run the benchmark, set SELFTEST=skip to run it anyway");
+        sink.metadata("verdict", "FAIL");
+        sink.finish();
+        return;
+    }
+    #[cfg(not(feature = "selftest"))]
+    sink.metadata("selftest", "skipped");
+
+    #[cfg(feature = "reset-peripherals")]
+    if !infra::peripherals::check(sink, &config.reset_peripherals) {
+        sink.metadata("verdict", "FAIL");
+        sink.finish();
+        return;
+    }
+
+    #[cfg(feature = "vfs-helpers")]
+    middleware::vfs::mount();
+
+    #[cfg(feature = "pmu")]
+    if infra::pmu::configure() {
+        let events: Vec<String> = infra::pmu::EVENTS.iter().map(|id| format!("{:#06x}", id)).collect();
+        sink.metadata("pmu_events", &events.join(","));
+    } else {
+        sink.metadata("pmu", "unavailable, the core has no PMU");
+    }
+
+    // Enabled again at the end of main
+    #[cfg(feature = "no-dcache")]
+    let _dcache = if infra::dcache::available() {
+        sink.metadata("dcache", "off");
+        infra::dcache::disable()
+    } else {
+        sink.metadata("dcache", "unavailable, the core has no data cache to disable");
+        None
+    };
+
+    #[cfg(feature = "helper-bench")]
+    middleware::helper_bench::run(sink);
+
+    // Kept until the end of main, so that the heap stays fragmented
+    #[cfg(all(feature = "fragment-heap", feature = "jit"))]
+    let _fragments = infra::fragment::fragment(sink, Some(core::mem::size_of::<AlignedBuffer>()));
+    #[cfg(all(feature = "fragment-heap", not(feature = "jit")))]
+    let _fragments = infra::fragment::fragment(sink, None);
+
+    // Released at the end of main
+    #[cfg(all(feature = "reserve-heap", feature = "jit"))]
+    let reservation = infra::reserve::reserve(sink, Some(core::mem::size_of::<AlignedBuffer>()));
+    #[cfg(all(feature = "reserve-heap", not(feature = "jit")))]
+    let reservation = infra::reserve::reserve(sink, None);
+    #[cfg(feature = "reserve-heap")]
+    let _reservation = match reservation {
+        Some(reservation) => reservation,
+        None => {
+            sink.metadata("verdict", "FAIL");
+            sink.finish();
+            return;
+        }
+    };
+
+    #[cfg(feature = "interference")]
+    let control = {
+        infra::interference::spawn();
+        let control = infra::interference::control();
+        infra::interference::start();
+        control
+    };
+
+    #[cfg(feature = "prometheus")]
+    let mut metrics = output::prometheus::Exposition::new();
+    #[cfg(feature = "error-summary")]
+    let mut errors = infra::error::ErrorTally::new();
+    #[cfg(feature = "criterion-csv")]
+    let mut criterion = output::criterion::CriterionCsv::new();
+
+    // Every VM kind listed in VM_KIND runs the whole benchmark
+    #[cfg(not(feature = "suite"))]
+    {
+        #[cfg(feature = "base64-load")]
+        let prog = infra::base64_load::receive(sink);
+        #[cfg(not(feature = "base64-load"))]
+        let prog = PROG;
+        #[cfg(feature = "stop-at-insn")]
+        let prog = match infra::breakpoint::patch(sink, prog) {
+            Some(prog) => prog,
+            None => {
+                sink.metadata("verdict", "FAIL");
+                sink.finish();
+                return;
+            }
         };
+        #[cfg(feature = "align-prog")]
+        let prog = infra::align::aligned_copy(sink, prog);
+        report_helpers(sink, prog);
+        #[cfg(feature = "placement")]
+        infra::placement::report(sink, prog);
+        #[cfg(feature = "verify-profile")]
+        middleware::verify_profile::run(sink, prog);
+        #[cfg(feature = "verify-reject")]
+        middleware::verify_reject::run(sink, prog);
+        #[cfg(feature = "load-throughput")]
+        infra::throughput::run(sink, &[("benchmark", prog)]);
+        #[cfg(feature = "verify-registration")]
+        middleware::verify_registration::run(sink, &[("benchmark", prog)]);
+        for &kind in &config.vm_kinds {
+            infra::vm::set(kind);
+            sink.metadata("vm_kind", kind.name());
+            sink.header(&header_columns(config));
+            let summary = run_program(sink, config, "benchmark", prog, iterations);
+            passed &= summary.report(sink);
+            #[cfg(feature = "error-summary")]
+            errors.merge(&summary.errors);
+            #[cfg(feature = "prometheus")]
+            metrics.add(&summary, VARIANT, kind.name());
+            #[cfg(feature = "criterion-csv")]
+            criterion.add(&summary, VARIANT, kind.name());
+            if summary.aborted {
+                break;
+            }
+        }
+    }
+
+    #[cfg(feature = "suite")]
+    {
+        // With a single header all programs form one table, the program and
+        // variant columns keep the rows apart.
+        #[cfg(feature = "single-header")]
+        sink.header(&header_columns(config));
 
-        #[cfg(feature = "libud")]
-        let mem = unsafe {
-            core::slice::from_raw_parts(
-                &ctx as *const Context as *const u8,
-                core::mem::size_of::<Context>(),
-            )
+        #[cfg(feature = "verify-profile")]
+        middleware::verify_profile::run(sink, infra::suite::PROGRAMS[0].bytes);
+        #[cfg(feature = "verify-reject")]
+        middleware::verify_reject::run(sink, infra::suite::PROGRAMS[0].bytes);
+        #[cfg(feature = "load-throughput")]
+        {
+            let programs: Vec<(&str, &[u8])> = infra::suite::PROGRAMS.iter().map(|p| (p.name, p.bytes)).collect();
+            infra::throughput::run(sink, &programs);
+        }
+        #[cfg(feature = "verify-registration")]
+        {
+            let programs: Vec<(&str, &[u8])> = infra::suite::PROGRAMS.iter().map(|p| (p.name, p.bytes)).collect();
+            middleware::verify_registration::run(sink, &programs);
+        }
+
+        'kinds: for &kind in &config.vm_kinds {
+            infra::vm::set(kind);
+            sink.metadata("vm_kind", kind.name());
+            let mut means: Vec<(&str, Option<u32>)> = Vec::new();
+            for program in infra::suite::PROGRAMS {
+                sink.metadata("program", program.name);
+                let bytes = program.bytes;
+                #[cfg(feature = "stop-at-insn")]
+                let bytes = match infra::breakpoint::patch(sink, bytes) {
+                    Some(bytes) => bytes,
+                    None => {
+                        passed = false;
+                        means.push((program.name, None));
+                        continue;
+                    }
+                };
+                #[cfg(feature = "align-prog")]
+                let bytes = infra::align::aligned_copy(sink, bytes);
+                report_helpers(sink, bytes);
+                #[cfg(feature = "placement")]
+                infra::placement::report(sink, bytes);
+                #[cfg(not(feature = "single-header"))]
+                sink.header(&header_columns(config));
+                let summary = run_program(sink, config, program.name, bytes, iterations);
+                passed &= summary.report(sink);
+                #[cfg(feature = "error-summary")]
+                errors.merge(&summary.errors);
+                #[cfg(feature = "prometheus")]
+                metrics.add(&summary, VARIANT, kind.name());
+                #[cfg(feature = "criterion-csv")]
+                criterion.add(&summary, VARIANT, kind.name());
+                if summary.aborted {
+                    break 'kinds;
+                }
+                means.push((program.name, summary.execution_time_us.mean()));
+            }
+            infra::suite::report_relative(sink, &means);
+            // After the tables, its lines would otherwise precede the header
+            #[cfg(feature = "icache-probe")]
+            run_icache_probe(sink, config, iterations);
+        }
+    }
+
+    #[cfg(feature = "interference")]
+    {
+        let benchmark = infra::interference::stop();
+        infra::interference::report(sink, "control", &control);
+        infra::interference::report(sink, "benchmark", &benchmark);
+    }
+
+    #[cfg(feature = "prometheus")]
+    metrics.write(sink);
+
+    #[cfg(feature = "criterion-csv")]
+    criterion.write(sink);
+
+    #[cfg(feature = "error-summary")]
+    errors.report(sink);
+
+    // For the timeouts of the host, covers the startup after the sleep up to
+    // the first row: the metadata, the checks and the first load. "na" if
+    // no row was sent
+    match FIRST_ROW_US.load(Ordering::Relaxed) {
+        NO_ROW => sink.metadata("time_to_first_row_us", "na"),
+        first_row_us => sink.metadata("time_to_first_row_us", &format!("{}", first_row_us.wrapping_sub(settled_us))),
+    }
+
+    sink.metadata("verdict", if passed { "PASS" } else { "FAIL" });
+
+    sink.finish();
+}
+
+/// Reports the helpers that the analysis found in the program, these are the
+/// only ones registered with the VM.
+fn report_helpers(sink: &mut dyn ResultSink, prog: &[u8]) {
+    let format = if cfg!(feature = "jit") {
+        ProgramFormat::RawObjectFile
+    } else {
+        ProgramFormat::FemtoContainersHeader
+    };
+    match referenced_helper_ids(prog, format) {
+        Some(ids) => {
+            let ids: Vec<String> = ids.iter().map(|id| format!("{:#04x}", id)).collect();
+            sink.metadata("helpers", &ids.join(","));
+        }
+        None => sink.metadata("helpers", "all (analysis inconclusive)"),
+    }
+    match referenced_helpers(prog, format) {
+        Ok(helpers) => sink.metadata("helpers_registered_count", &format!("{}", registered_helper_count(&helpers))),
+        // The program is refused with the id of the helper
+        Err(_) => sink.metadata("helpers_registered_count", "none, the program calls a helper which is not implemented"),
+    }
+}
+
+/// Number of helpers registered with the VM for the program, out of the
+/// `ALL_HELPERS` table. The VFS helpers are registered in addition.
+fn registered_helper_count(helpers: &[HelperFunction]) -> usize {
+    let count = helpers.len();
+    #[cfg(feature = "vfs-helpers")]
+    let count = count + middleware::vfs::HELPERS.len();
+    count
+}
+
+fn header_columns(config: &Config) -> Vec<&'static str> {
+    let mut columns = vec![
+        "iteration",
+        "init_runtime_us",
+        "load_program_us",
+        "execution_time_us",
+        "correct",
+        "register_us",
+        "sig_verify_us",
+        "mbuff_len",
+        "gap_us",
+    ];
+    #[cfg(feature = "header-cost")]
+    columns.extend_from_slice(&["raw_load_program_us", "header_overhead_us", "results_match"]);
+    #[cfg(feature = "ns-precision")]
+    columns.push("execution_time_ns");
+    #[cfg(feature = "reverify")]
+    columns.push("corrupted");
+    #[cfg(feature = "helper-pattern")]
+    columns.extend_from_slice(&["helpers_registered", "helpers_registered_count", "verified"]);
+    #[cfg(feature = "exec-batch")]
+    columns.extend_from_slice(&["exec_batch", "per_call_ns"]);
+    #[cfg(feature = "canary-check")]
+    columns.push("stack_ok");
+    #[cfg(feature = "pmu")]
+    {
+        columns.extend_from_slice(infra::pmu::COLUMNS);
+        columns.push("pmu_suspect");
+    }
+    #[cfg(feature = "trigger")]
+    columns.push("wakeup_delay_us");
+    #[cfg(feature = "jit")]
+    columns.push("jit_finalize_us");
+    #[cfg(feature = "check-cost")]
+    columns.push("check_us");
+    #[cfg(feature = "helper-cold")]
+    columns.extend_from_slice(&["second_execution_us", "helper_cold_delta_us"]);
+    #[cfg(feature = "compare-mem")]
+    columns.extend_from_slice(&["mem_mismatch", "mem_mismatch_offset"]);
+    #[cfg(feature = "stop-at-insn")]
+    columns.push("stop_at_insn");
+    #[cfg(feature = "prewarm-mbuff")]
+    columns.push("mbuff_prewarmed");
+    #[cfg(feature = "mbuff-guard")]
+    columns.push("guard_ok");
+    #[cfg(feature = "unwrap")]
+    columns.push("unwrap_us");
+    #[cfg(feature = "seeds")]
+    columns.push("seed");
+    #[cfg(feature = "reference")]
+    columns.push("matches_native");
+    #[cfg(feature = "register-overhead")]
+    columns.extend_from_slice(&["register_fresh_us", "register_overhead_us"]);
+    if config.region_count > 0 {
+        columns.push("region_count");
+    }
+    if config.concurrent_vms > 1 {
+        columns.push("vm");
+    }
+    if config.vm_kinds.len() > 1 {
+        columns.push("vm_kind");
+    }
+    if config.label_column {
+        columns.push("label");
+    }
+    #[cfg(feature = "suite")]
+    columns.extend_from_slice(&["program", "variant"]);
+    columns
+}
+
+/// Timings and results of a single iteration.
+struct Measurement {
+    load_program_us: u32,
+    register_us: u32,
+    /// 0 unless the signed-programs feature is enabled.
+    sig_verify_us: u32,
+    /// Length of the mbuff handed to the program, the JIT gets none.
+    mbuff_len: usize,
+    /// Time between the end of the load and the start of the execution
+    /// measurement, which is in neither of them.
+    gap_us: u32,
+    execution_time_us: u32,
+    correct: bool,
+    #[cfg(feature = "header-cost")]
+    raw_load_program_us: u32,
+    #[cfg(feature = "header-cost")]
+    results_match: bool,
+    /// `None` if the timer doesn't resolve below a microsecond.
+    #[cfg(feature = "ns-precision")]
+    execution_time_ns: Option<u64>,
+    /// Verification failed after the execution or the program bytes changed.
+    #[cfg(feature = "reverify")]
+    corrupted: bool,
+    #[cfg(feature = "helper-pattern")]
+    helpers_registered: bool,
+    /// 0 in the iterations without registration.
+    #[cfg(feature = "helper-pattern")]
+    helpers_registered_count: usize,
+    /// With helper-pattern a program may fail verification, it is not
+    /// executed then.
+    #[cfg(feature = "helper-pattern")]
+    verified: bool,
+    /// With exec-batch the execution time covers all executions of the batch.
+    #[cfg(feature = "exec-batch")]
+    per_call_ns: u64,
+    /// The canary at the bottom of the thread's stack was intact after the
+    /// iteration.
+    #[cfg(feature = "canary-check")]
+    stack_ok: bool,
+    /// `None` on cores without a PMU.
+    #[cfg(feature = "pmu")]
+    pmu: Option<infra::pmu::Sample>,
+    /// Time at which the execution started, to compute the wake-up delay.
+    #[cfg(feature = "trigger")]
+    execution_start_us: u32,
+    /// Set by the event-driven mode once the trigger time is known.
+    #[cfg(feature = "trigger")]
+    wakeup_delay_us: u32,
+    /// Time to get the callable function out of the compiled code, part of
+    /// the load time.
+    #[cfg(feature = "jit")]
+    jit_finalize_us: u32,
+    /// Time of a second execution with the same VM, `None` for programs
+    /// without helper calls.
+    #[cfg(feature = "helper-cold")]
+    second_execution_us: Option<u32>,
+    /// Time to check the results of the executions, after the execution
+    /// measurement.
+    #[cfg(feature = "check-cost")]
+    check_us: u32,
+    /// First byte of the program-visible memory the raw variant left
+    /// different from the header variant.
+    #[cfg(feature = "compare-mem")]
+    mem_mismatch: Option<usize>,
+    /// Whether the mbuff was read right before the execution.
+    #[cfg(feature = "prewarm-mbuff")]
+    mbuff_prewarmed: bool,
+    /// Whether the guard after the program's memory was left intact.
+    #[cfg(feature = "mbuff-guard")]
+    guard_ok: bool,
+    /// Time to validate the wrapped program before the load.
+    #[cfg(feature = "unwrap")]
+    unwrap_us: u32,
+    /// Seed of the Context of the iteration.
+    #[cfg(feature = "seeds")]
+    seed: u64,
+    /// Whether the executions returned the result of the native reference
+    /// on the same input.
+    #[cfg(feature = "reference")]
+    matches_native: bool,
+    /// Registration of all helpers into a fresh VM, and what it costs more
+    /// than the share of registering them once.
+    #[cfg(feature = "register-overhead")]
+    register_fresh_us: u32,
+    #[cfg(feature = "register-overhead")]
+    register_overhead_us: i64,
+    /// An execution of the iteration failed in rbpf, tallied as
+    /// `execution_failed` in the error summary.
+    #[cfg(feature = "error-summary")]
+    execution_failed: bool,
+    /// Copy of the Context after the execution, if it is dumped.
+    #[cfg(feature = "dump-context")]
+    context: Option<Box<Context>>,
+    /// The first canary around the program-visible memory that was
+    /// overwritten, with the name of the memory.
+    canary: Option<(&'static str, Damage)>,
+}
+
+/// Reports the damaged canary of an iteration. Returns true if there is one,
+/// the run must not continue then.
+fn report_damage(sink: &mut dyn ResultSink, m: &Measurement) -> bool {
+    match &m.canary {
+        Some((memory, damage)) => {
+            sink.comment(&format!(
+                "canary=corrupted memory={} offset={} value={:#010x}, aborting",
+                memory, damage.offset, damage.value
+            ));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Emits the row of a recorded iteration and adds it to the summary.
+/// Returns false if the run has to stop.
+fn record(
+    sink: &mut dyn ResultSink,
+    summary: &mut ProgramSummary,
+    i: usize,
+    result: &Result<Measurement, BenchError>,
+    config: &Config,
+    vm: Option<usize>,
+) -> bool {
+    match result {
+        Ok(m) => {
+            if FIRST_ROW_US.load(Ordering::Relaxed) == NO_ROW {
+                FIRST_ROW_US.store(util::time::now_us(), Ordering::Relaxed);
+            }
+            sink.row(&row_data(config, i, m, &summary.name, vm));
+            #[cfg(feature = "dump-context")]
+            if let Some(ctx) = &m.context {
+                infra::context_dump::report(sink, i, ctx);
+            }
+            #[cfg(feature = "stream")]
+            infra::stream::wait_ack(sink, i);
+            summary.add(m.load_program_us, m.execution_time_us, m.correct);
+            #[cfg(feature = "error-summary")]
+            if m.execution_failed {
+                summary.errors.add_code(&summary.name, i, "execution_failed");
+            }
+            #[cfg(feature = "top-slowest")]
+            summary.slowest.add(i, m.execution_time_us);
+            #[cfg(feature = "burst")]
+            if let Some(tail_us) = summary.bursts.add(m.execution_time_us) {
+                sink.comment(&format!("burst run={} tail_us={}", i, tail_us));
+            }
+            // Warns once per excursion above the baseline, not for every
+            // iteration of it
+            if let Some(drift) = summary.drift.as_mut() {
+                if drift.add(m.execution_time_us) {
+                    sink.banner(&format!("DRIFT WARNING at iteration {}", i));
+                    sink.comment(&format!(
+                        "drift run={} execution_time_us={} baseline_us={}",
+                        i,
+                        m.execution_time_us,
+                        drift.baseline_us().unwrap_or(0)
+                    ));
+                }
+            }
+            // The canary stays overwritten, only the first iteration is of
+            // interest.
+            #[cfg(feature = "canary-check")]
+            if !m.stack_ok && summary.stack_violation.is_none() {
+                summary.stack_violation = Some(i);
+                sink.comment(&format!("stack=overflow canary overwritten in run={}", i));
+            }
+            if report_damage(sink, m) {
+                summary.abort();
+                return false;
+            }
+            true
+        }
+        Err(error) => {
+            error.report(sink);
+            #[cfg(feature = "error-summary")]
+            summary.errors.add(&summary.name, i, error);
+            if !error.is_transient() {
+                summary.fail();
+                false
+            } else if config.robust {
+                sink.comment(&format!("skipped run={}", i));
+                true
+            } else {
+                summary.abort();
+                false
+            }
+        }
+    }
+}
+
+/// Time the first row was sent, `NO_ROW` before.
+static FIRST_ROW_US: AtomicU32 = AtomicU32::new(NO_ROW);
+const NO_ROW: u32 = u32::MAX;
+
+/// Iterations run for the current program, bounded by `MAX_ITERATIONS`.
+static ITERATIONS_RUN: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts an iteration towards `MAX_ITERATIONS`. Returns false once the cap
+/// is reached, the banner is printed the first time.
+fn within_cap(sink: &mut dyn ResultSink, config: &Config) -> bool {
+    let run = ITERATIONS_RUN.fetch_add(1, Ordering::Relaxed);
+    if run < config.max_iterations {
+        return true;
+    }
+    if run == config.max_iterations {
+        sink.banner("Hit iteration cap");
+        sink.comment(&format!("max_iterations={} reached, the remaining iterations are skipped", run));
+    }
+    false
+}
+
+/// Runs an iteration whose results are not recorded. In robust mode an
+/// iteration with a transient error is skipped, otherwise the error is
+/// returned.
+fn run_discarded(sink: &mut dyn ResultSink, prog: &PreparedProgram) -> Result<Option<Measurement>, BenchError> {
+    match run_iteration(prog) {
+        Ok(measurement) => {
+            // Aborts the run like a recorded iteration, see `record`
+            if report_damage(sink, &measurement) {
+                return Err(BenchError::MemorySafety);
+            }
+            Ok(Some(measurement))
+        }
+        Err(error) => {
+            error.report(sink);
+            if error.is_transient() && prog.config.robust {
+                Ok(None)
+            } else {
+                Err(error)
+            }
+        }
+    }
+}
+
+/// A program with what its iterations need that is known before the first
+/// one, so that no iteration spends its time finding it again.
+#[derive(Clone)]
+struct PreparedProgram {
+    bytes: &'static [u8],
+    config: &'static Config,
+    /// The helpers the program calls, registered in every iteration
+    /// (profiling shims with helper-profile).
+    helpers: Vec<HelperFunction>,
+    /// The helpers of RAW_PROG, loaded after the program in every iteration.
+    #[cfg(feature = "header-cost")]
+    raw_helpers: Vec<HelperFunction>,
+}
+
+impl PreparedProgram {
+    /// Runs the analysis of `bytes` once. Fails if the program calls a helper
+    /// that isn't implemented, before any iteration loads it.
+    fn prepare(bytes: &'static [u8], config: &'static Config) -> Result<Self, BenchError> {
+        let format = if cfg!(feature = "jit") {
+            ProgramFormat::RawObjectFile
+        } else {
+            ProgramFormat::FemtoContainersHeader
         };
+        let helpers = referenced_helpers(bytes, format)?;
+        #[cfg(feature = "helper-profile")]
+        let helpers = middleware::profile::instrument(helpers);
+        Ok(PreparedProgram {
+            bytes,
+            config,
+            helpers,
+            #[cfg(feature = "header-cost")]
+            raw_helpers: referenced_helpers(RAW_PROG, ProgramFormat::RawObjectFile)?,
+        })
+    }
+}
 
-        #[cfg(not(feature = "libud"))]
-        let mem: &[u8] = &[]; // Default empty slice if not libud
+/// Runs all iterations of a single program, emitting one row per iteration.
+fn run_program(
+    sink: &mut dyn ResultSink,
+    config: &'static Config,
+    name: &str,
+    prog: &'static [u8],
+    iterations: usize,
+) -> ProgramSummary {
+    #[cfg(feature = "dump-samples")]
+    infra::samples::check(sink, name);
+    #[cfg(feature = "unwrap")]
+    infra::unwrap::record(prog);
+    #[cfg(feature = "register-overhead")]
+    infra::register_overhead::register_once(prog);
+    let mut summary = ProgramSummary::new(name);
+    ITERATIONS_RUN.store(0, Ordering::Relaxed);
 
-        let load_program_duration = micro_sec
-            .time(|| {
-                #[cfg(not(feature = "jit"))]
-                {
-                    vm = Some(
-                        EbpfVmMbuff::new(Some(prog), rbpf::InterpreterVariant::FemtoContainersHeader)
-                            .expect("failed to load program"),
-                    );
-                    register_all(vm.as_mut().unwrap());
-                    vm.as_ref().unwrap().verify_loaded_program().expect("program verification failed");
+    let prog = match PreparedProgram::prepare(prog, config) {
+        Ok(prog) => prog,
+        Err(error) => {
+            error.report(sink);
+            summary.fail();
+            return summary;
+        }
+    };
+    let prog = &prog;
+
+    if cfg!(feature = "jit") && infra::vm::current() != infra::vm::VmKind::Mbuff {
+        sink.comment("vm_kind=refused the JIT doesn't use the rbpf VM types, VM_KIND only applies to the interpreter");
+        summary.fail();
+        return summary;
+    }
+
+    #[cfg(feature = "confidence")]
+    if config.concurrent_vms > 1 {
+        sink.comment("confidence=refused CONFIDENCE_PCT decides after each iteration on the main thread, set CONCURRENT_VMS=1");
+        summary.fail();
+        return summary;
+    }
+
+    #[cfg(feature = "trigger")]
+    if config.concurrent_vms > 1 {
+        sink.comment("trigger=refused TRIGGER_PERIOD_MS runs the iterations on the main thread only, set CONCURRENT_VMS=1");
+        summary.fail();
+        return summary;
+    }
+
+    #[cfg(feature = "verify-first")]
+    match verify_first(sink, prog) {
+        Ok(true) => {}
+        Ok(false) => {
+            sink.banner("Skipping timing: correctness failed");
+            summary.fail();
+            return summary;
+        }
+        Err(error) => {
+            if error.aborts_run() {
+                summary.abort();
+            } else {
+                summary.fail();
+            }
+            return summary;
+        }
+    }
+
+    #[cfg(feature = "sanity")]
+    let prepared = sanity_check(sink, prog).and_then(|_| run_warmup(sink, prog));
+    #[cfg(not(feature = "sanity"))]
+    let prepared = run_warmup(sink, prog);
+    if let Err(error) = prepared {
+        if error.aborts_run() {
+            summary.abort();
+        } else {
+            summary.fail();
+        }
+        return summary;
+    }
+
+    reset_recorded_state();
+
+    let vms = config.concurrent_vms;
+    if vms > 1 {
+        // Without multithread `Config::parse` refuses more than one VM
+        #[cfg(feature = "multithread")]
+        run_concurrent(sink, &mut summary, prog, iterations, vms);
+    } else {
+        #[cfg(feature = "trigger")]
+        run_triggered(sink, &mut summary, prog, iterations);
+        // With confidence the iterations go on until the interval is narrow
+        // enough, bounded by MAX_ITERATIONS instead of ITERATIONS
+        #[cfg(not(feature = "trigger"))]
+        run_sequential(sink, &mut summary, prog, if cfg!(feature = "confidence") { usize::MAX } else { iterations });
+    }
+
+    #[cfg(feature = "helper-profile")]
+    report_helper_profile(sink, &summary);
+
+    #[cfg(feature = "vfs-helpers")]
+    report_vfs_throughput(sink, &summary);
+
+    summary
+}
+
+/// Resets the state collected over the recorded iterations of a program,
+/// before the first one.
+fn reset_recorded_state() {
+    #[cfg(feature = "helper-profile")]
+    middleware::profile::reset();
+
+    #[cfg(feature = "helper-pattern")]
+    middleware::registration::reset();
+
+    #[cfg(feature = "vfs-helpers")]
+    middleware::vfs::reset_bytes_read();
+
+    #[cfg(feature = "dump-context")]
+    infra::context_dump::arm();
+
+    #[cfg(feature = "prewarm-mbuff")]
+    infra::prewarm::reset();
+
+    #[cfg(feature = "seeds")]
+    infra::seeds::reset();
+}
+
+/// Runs the iterations back to back. A failed or incorrect iteration within
+/// the first `RESTART_ON_EARLY_FAILURE` ones starts them again from 0 with a
+/// fresh summary, at most `MAX_RESTARTS` times. Iterations which damaged a
+/// canary or can't succeed at all are never restarted.
+#[cfg(not(feature = "trigger"))]
+fn run_sequential(sink: &mut dyn ResultSink, summary: &mut ProgramSummary, prog: &PreparedProgram, iterations: usize) {
+    let config = prog.config;
+    let mut restarts = 0;
+    let mut i = 0;
+    while i < iterations {
+        if !within_cap(sink, config) {
+            break;
+        }
+        #[cfg(feature = "iter-markers")]
+        sink.text(&format!("<<iter {}>>", i));
+        let result = run_iteration(prog);
+        let proceed = record(sink, summary, i, &result, config, None);
+        #[cfg(feature = "iter-markers")]
+        sink.text(&format!("<<end {}>>", i));
+        let early_failure = match &result {
+            Ok(m) => !m.correct && m.canary.is_none(),
+            Err(error) => error.is_transient(),
+        };
+        if early_failure && i < config.restart_on_early_failure && restarts < config.max_restarts {
+            restarts += 1;
+            sink.banner("Restarting");
+            sink.comment(&format!(
+                "restart program={} run={} restart={} max_restarts={}",
+                summary.name,
+                i,
+                restarts,
+                config.max_restarts
+            ));
+            *summary = ProgramSummary::new(&summary.name);
+            // Also clears the helper profile, the calls of the aborted
+            // attempt aren't part of the recorded runs
+            reset_recorded_state();
+            i = 0;
+            continue;
+        }
+        if !proceed {
+            break;
+        }
+        #[cfg(feature = "membw")]
+        if (i + 1) % config.membw_every == 0 {
+            infra::membw::report(sink, i);
+        }
+        #[cfg(feature = "confidence")]
+        if summary.confidence.within(config.confidence_pct) {
+            sink.banner("Confidence reached");
+            break;
+        }
+        i += 1;
+    }
+}
+
+/// Runs one iteration per event of the periodic trigger instead of back to
+/// back. The iteration is done once its row is emitted, a trigger before that
+/// is a missed deadline.
+#[cfg(feature = "trigger")]
+fn run_triggered(sink: &mut dyn ResultSink, summary: &mut ProgramSummary, prog: &PreparedProgram, iterations: usize) {
+    infra::trigger::start();
+    for i in 0..iterations {
+        if !within_cap(sink, prog.config) {
+            break;
+        }
+        // Before the wait, printing would delay the execution
+        #[cfg(feature = "iter-markers")]
+        sink.text(&format!("<<iter {}>>", i));
+        let fired_us = infra::trigger::wait();
+        let mut result = run_iteration(prog);
+        if let Ok(m) = &mut result {
+            m.wakeup_delay_us = m.execution_start_us.wrapping_sub(fired_us);
+            summary.wakeup_delay_us.add(m.wakeup_delay_us);
+        }
+        let proceed = record(sink, summary, i, &result, prog.config, None);
+        #[cfg(feature = "iter-markers")]
+        sink.text(&format!("<<end {}>>", i));
+        infra::trigger::done();
+        if !proceed {
+            break;
+        }
+    }
+    summary.missed_deadlines = infra::trigger::stop();
+}
+
+/// Runs the first two programs of the suite alternately, one iteration of
+/// each in turn, and each on its own, and reports how much slower each is
+/// when the other ran in between: the cost of the program being evicted from
+/// the caches, its code with the JIT and its bytecode with the interpreter.
+/// The iterations aren't recorded, the results are `icache_probe` metadata.
+#[cfg(feature = "icache-probe")]
+fn run_icache_probe(sink: &mut dyn ResultSink, config: &'static Config, iterations: usize) {
+    let (a, b) = match infra::suite::PROGRAMS {
+        [a, b, ..] => (a, b),
+        _ => {
+            sink.metadata("icache_probe", "skipped SUITE needs at least two programs");
+            return;
+        }
+    };
+    let (a_prog, b_prog) = match (PreparedProgram::prepare(a.bytes, config), PreparedProgram::prepare(b.bytes, config)) {
+        (Ok(a_prog), Ok(b_prog)) => (a_prog, b_prog),
+        (Err(error), _) | (_, Err(error)) => {
+            error.report(sink);
+            sink.metadata("icache_probe", "failed a program can't be loaded, no penalty");
+            return;
+        }
+    };
+    let mut time = |prog: &PreparedProgram, times: &mut Accumulator| -> bool {
+        match run_discarded(sink, prog) {
+            Ok(Some(m)) => {
+                times.add(m.execution_time_us);
+                true
+            }
+            Ok(None) => true,
+            Err(_) => false,
+        }
+    };
+    let (mut a_isolated, mut b_isolated) = (Accumulator::new(), Accumulator::new());
+    let (mut a_alternated, mut b_alternated) = (Accumulator::new(), Accumulator::new());
+    let completed = (0..iterations).all(|_| time(&a_prog, &mut a_isolated))
+        && (0..iterations).all(|_| time(&b_prog, &mut b_isolated))
+        && (0..iterations).all(|_| time(&a_prog, &mut a_alternated) && time(&b_prog, &mut b_alternated));
+    if !completed {
+        sink.metadata("icache_probe", "failed an iteration failed, no penalty");
+        return;
+    }
+    for (program, isolated, alternated) in [(a, a_isolated, a_alternated), (b, b_isolated, b_alternated)].iter() {
+        let (isolated, alternated) = match (isolated.mean(), alternated.mean()) {
+            (Some(isolated), Some(alternated)) => (isolated, alternated),
+            _ => continue,
+        };
+        let penalty_us = alternated as i64 - isolated as i64;
+        sink.metadata(
+            "icache_probe",
+            &format!(
+                "program={} other={} runs={} isolated_mean_us={} alternated_mean_us={} penalty_us={} penalty={}",
+                program.name,
+                if program.name == a.name { b.name } else { a.name },
+                iterations,
+                isolated,
+                alternated,
+                penalty_us,
+                infra::baseline::Percent(penalty_us * 10_000 / isolated.max(1) as i64)
+            ),
+        );
+    }
+}
+
+/// Reports the rate at which the program read through the VFS helpers, for
+/// programs which read at all.
+#[cfg(feature = "vfs-helpers")]
+fn report_vfs_throughput(sink: &mut dyn ResultSink, summary: &ProgramSummary) {
+    let bytes = middleware::vfs::bytes_read();
+    if bytes == 0 {
+        return;
+    }
+    sink.comment(&format!(
+        "vfs program={} bytes_read={} bytes_per_s={}",
+        summary.name,
+        bytes,
+        bytes * 1_000_000 / summary.execution_time_us.sum().max(1)
+    ));
+}
+
+/// Reports the time attributed to each helper over the recorded iterations
+/// of a program, one line per helper. The time per run is over the rows
+/// recorded, of every VM with CONCURRENT_VMS, not the configured iterations.
+#[cfg(feature = "helper-profile")]
+fn report_helper_profile(sink: &mut dyn ResultSink, summary: &ProgramSummary) {
+    let runs = summary.execution_time_us.count();
+    for profile in middleware::profile::snapshot() {
+        sink.comment(&format!(
+            "helper_profile program={} helper={:#04x} calls={} total_us={} per_run_us={}",
+            summary.name,
+            profile.id as u32,
+            profile.calls,
+            profile.total_us,
+            profile.total_us / runs.max(1)
+        ));
+    }
+}
+
+/// Runs the program once to check its result before any timed iteration.
+/// Without a result, e.g. a skipped iteration in robust mode, the program
+/// counts as incorrect as well.
+#[cfg(feature = "verify-first")]
+fn verify_first(sink: &mut dyn ResultSink, prog: &PreparedProgram) -> Result<bool, BenchError> {
+    let correct = match run_discarded(sink, prog)? {
+        Some(measurement) => measurement.correct,
+        None => false,
+    };
+    sink.comment(&format!("verify_first correct={}", correct));
+    Ok(correct)
+}
+
+/// Runs the program once and warns if it looks like it didn't do anything,
+/// e.g. because of a broken header the VM returns before running any code.
+/// rbpf doesn't report how many instructions were executed, so the execution
+/// time is the only indicator.
+#[cfg(feature = "sanity")]
+fn sanity_check(sink: &mut dyn ResultSink, prog: &PreparedProgram) -> Result<(), BenchError> {
+    let min_us = prog.config.sanity_min_us;
+    let measurement = match run_discarded(sink, prog)? {
+        Some(measurement) => measurement,
+        None => {
+            sink.metadata("sanity", "skipped");
+            return Ok(());
+        }
+    };
+    if measurement.execution_time_us < min_us {
+        sink.comment(&format!(
+            "sanity=warning execution_time_us={} is below SANITY_MIN_US={}, the program appears to be a no-op",
+            measurement.execution_time_us, min_us
+        ));
+    } else {
+        sink.comment(&format!("sanity=ok execution_time_us={}", measurement.execution_time_us));
+    }
+    Ok(())
+}
+
+/// Runs the iterations that are discarded before the recorded run.
+fn run_warmup(sink: &mut dyn ResultSink, prog: &PreparedProgram) -> Result<(), BenchError> {
+    match prog.config.warmup {
+        Warmup::None => {}
+        Warmup::Fixed(count) => {
+            for _ in 0..count {
+                if !within_cap(sink, prog.config) {
+                    break;
                 }
+                run_discarded(sink, prog)?;
+            }
+            sink.comment(&format!("warmup={} iterations discarded", count));
+        }
+        Warmup::Auto { window, spread_pct, max } => {
+            let (discarded, stable) = StabilityDetector::new(window, spread_pct).settle(max, || {
+                if !within_cap(sink, prog.config) {
+                    return Ok(Run::Stop);
+                }
+                Ok(match run_discarded(sink, prog)? {
+                    Some(measurement) => Run::Sample(measurement.execution_time_us),
+                    None => Run::Skipped,
+                })
+            })?;
+            sink.comment(&format!("warmup_auto={} iterations discarded", discarded));
+            if !stable {
+                sink.comment(&format!("warmup_auto did not stabilise, stopped at the cap of {}", max));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Stack of a concurrent VM thread, it holds the Context besides the
+/// interpreter.
+#[cfg(all(feature = "multithread", feature = "libud"))]
+const VM_STACK_SIZE: usize = 4096 + core::mem::size_of::<Guarded<Context>>();
+#[cfg(all(feature = "multithread", not(feature = "libud")))]
+const VM_STACK_SIZE: usize = 4096;
+
+/// Runs the iterations on several VMs at once and compares the throughput to
+/// a single VM run the same way.
+#[cfg(feature = "multithread")]
+fn run_concurrent(
+    sink: &mut dyn ResultSink,
+    summary: &mut ProgramSummary,
+    prog: &PreparedProgram,
+    iterations: usize,
+    vms: usize,
+) {
+    use infra::concurrent;
+
+    if cfg!(feature = "jit") {
+        sink.comment("concurrent=refused CONCURRENT_VMS is only supported by the interpreter");
+        return summary.fail();
+    }
+    if vms > concurrent::MAX_VMS {
+        sink.comment(&format!(
+            "concurrent=refused CONCURRENT_VMS={} exceeds the maximum of {}",
+            vms,
+            concurrent::MAX_VMS
+        ));
+        return summary.fail();
+    }
+    if let Err(required) = concurrent::check_heap::<PreparedProgram, Result<Measurement, BenchError>>(vms, iterations, VM_STACK_SIZE) {
+        sink.comment(&format!(
+            "concurrent=refused {} VMs need {} bytes of heap, reduce CONCURRENT_VMS or ITERATIONS",
+            vms, required
+        ));
+        return summary.fail();
+    }
+
+    // The threads need the program for as long as they exist, it is leaked
+    // like their synchronisation state, this mode runs once per program
+    let prog: &'static PreparedProgram = Box::leak(Box::new(prog.clone()));
+    let single = concurrent::run(1, iterations, VM_STACK_SIZE, prog, run_iteration);
+    let run = concurrent::run(vms, iterations, VM_STACK_SIZE, prog, run_iteration);
+
+    'rows: for (vm, rows) in run.rows.iter().enumerate() {
+        for (i, result) in rows.iter().enumerate() {
+            if !record(sink, summary, i, result, prog.config, Some(vm)) {
+                break 'rows;
+            }
+        }
+    }
+
+    // Executions per second, load and execution of a program count as one
+    let throughput = |runs: usize, wall_us: u32| (runs as u64 * 1_000_000 / wall_us.max(1) as u64) as u32;
+    let single_per_s = throughput(iterations, single.wall_us);
+    let per_s = throughput(vms * iterations, run.wall_us);
+    sink.comment(&format!(
+        "concurrent program={} vms={} runs={} wall_us={} runs_per_s={} single_vm_runs_per_s={} scaling_pct={}",
+        summary.name,
+        vms,
+        vms * iterations,
+        run.wall_us,
+        per_s,
+        single_per_s,
+        per_s as u64 * 100 / single_per_s.max(1) as u64
+    ));
+
+    // Timings of each thread, an unfair scheduling shows as an uneven spread
+    for (vm, rows) in run.rows.iter().enumerate() {
+        let mut load_us = Accumulator::new();
+        let mut execution_us = Accumulator::new();
+        for m in rows.iter().flatten() {
+            load_us.add(m.load_program_us);
+            execution_us.add(m.execution_time_us);
+        }
+        let wall_us = run.worker_wall_us[vm];
+        sink.comment(&format!(
+            "concurrent_vm program={} vm={} runs={} wall_us={} runs_per_s={} load_mean_us={} exec_mean_us={} exec_max_us={}",
+            summary.name,
+            vm,
+            execution_us.count(),
+            wall_us,
+            throughput(execution_us.count() as usize, wall_us),
+            load_us.mean().unwrap_or(0),
+            execution_us.mean().unwrap_or(0),
+            execution_us.max().unwrap_or(0)
+        ));
+    }
+}
+
+/// The fields of an iteration in the order of `header_columns`.
+#[cfg_attr(not(feature = "suite"), allow(unused_variables))]
+fn row_data<'a>(config: &Config, i: usize, m: &Measurement, name: &'a str, vm: Option<usize>) -> RowData<'a> {
+    let mut row = RowData::new();
+    row.push(Value::UInt(i as u64));
+    row.push(Value::UInt(0)); // init runtime not applicable here
+    row.push(Value::UInt(m.load_program_us as u64));
+    row.push(Value::UInt(m.execution_time_us as u64));
+    row.push(Value::Bool(m.correct));
+    row.push(Value::UInt(m.register_us as u64));
+    row.push(Value::UInt(m.sig_verify_us as u64));
+    row.push(Value::UInt(m.mbuff_len as u64));
+    row.push(Value::UInt(m.gap_us as u64));
+
+    #[cfg(feature = "header-cost")]
+    {
+        let header_overhead = m.load_program_us as i64 - m.raw_load_program_us as i64;
+        row.push(Value::UInt(m.raw_load_program_us as u64));
+        row.push(Value::Int(header_overhead));
+        row.push(Value::Bool(m.results_match));
+    }
+
+    #[cfg(feature = "ns-precision")]
+    row.push(match m.execution_time_ns {
+        Some(ns) => Value::UInt(ns),
+        None => Value::Na,
+    });
+
+    #[cfg(feature = "reverify")]
+    row.push(Value::Bool(m.corrupted));
+
+    #[cfg(feature = "helper-pattern")]
+    {
+        row.push(Value::Bool(m.helpers_registered));
+        row.push(Value::UInt(m.helpers_registered_count as u64));
+        row.push(Value::Bool(m.verified));
+    }
+
+    #[cfg(feature = "exec-batch")]
+    {
+        row.push(Value::UInt(config.exec_batch as u64));
+        row.push(Value::UInt(m.per_call_ns));
+    }
+
+    #[cfg(feature = "canary-check")]
+    row.push(Value::Bool(m.stack_ok));
+
+    #[cfg(feature = "pmu")]
+    match &m.pmu {
+        Some(sample) => {
+            for count in &sample.counts[..infra::pmu::EVENTS.len()] {
+                row.push(Value::UInt(*count as u64));
+            }
+            row.push(Value::Bool(sample.overflowed));
+        }
+        None => {
+            for _ in 0..=infra::pmu::EVENTS.len() {
+                row.push(Value::Na);
+            }
+        }
+    }
+
+    #[cfg(feature = "trigger")]
+    row.push(Value::UInt(m.wakeup_delay_us as u64));
+
+    #[cfg(feature = "jit")]
+    row.push(Value::UInt(m.jit_finalize_us as u64));
+
+    #[cfg(feature = "check-cost")]
+    row.push(Value::UInt(m.check_us as u64));
+
+    // With exec-batch the mean execution of the batch is compared
+    #[cfg(feature = "helper-cold")]
+    match m.second_execution_us {
+        Some(second) => {
+            #[cfg(feature = "exec-batch")]
+            let first = m.execution_time_us / config.exec_batch;
+            #[cfg(not(feature = "exec-batch"))]
+            let first = m.execution_time_us;
+            row.push(Value::UInt(second as u64));
+            row.push(Value::Int(first as i64 - second as i64));
+        }
+        None => {
+            row.push(Value::Na);
+            row.push(Value::Na);
+        }
+    }
+
+    #[cfg(feature = "compare-mem")]
+    {
+        row.push(Value::Bool(m.mem_mismatch.is_some()));
+        row.push(match m.mem_mismatch {
+            Some(offset) => Value::UInt(offset as u64),
+            None => Value::Na,
+        });
+    }
+
+    #[cfg(feature = "stop-at-insn")]
+    row.push(Value::UInt(config.stop_at_insn as u64));
+
+    #[cfg(feature = "prewarm-mbuff")]
+    row.push(Value::Bool(m.mbuff_prewarmed));
+
+    #[cfg(feature = "mbuff-guard")]
+    row.push(Value::Bool(m.guard_ok));
+
+    #[cfg(feature = "unwrap")]
+    row.push(Value::UInt(m.unwrap_us as u64));
+
+    #[cfg(feature = "seeds")]
+    row.push(Value::UInt(m.seed));
+
+    #[cfg(feature = "reference")]
+    row.push(Value::Bool(m.matches_native));
+
+    #[cfg(feature = "register-overhead")]
+    {
+        row.push(Value::UInt(m.register_fresh_us as u64));
+        row.push(Value::Int(m.register_overhead_us));
+    }
+
+    if config.region_count > 0 {
+        row.push(Value::UInt(config.region_count as u64));
+    }
+    if let Some(vm) = vm {
+        row.push(Value::UInt(vm as u64));
+    }
+    if config.vm_kinds.len() > 1 {
+        row.push(Value::Str(infra::vm::current().name()));
+    }
+
+    if config.label_column {
+        row.push(Value::Str(config.run_label));
+    }
+
+    #[cfg(feature = "suite")]
+    {
+        row.push(Value::Str(name));
+        row.push(Value::Str(VARIANT));
+    }
+
+    row
+}
+
+/// Size of the buffer the memory access program works on, in words.
+const REGION_WORDS: usize = 64;
+
+/// Never accessed, only makes the dummy regions valid.
+static DUMMY_REGION: [u8; 8] = [0; 8];
+
+/// The allowed memory regions as (address, length): `count - 1` dummy
+/// regions followed by the buffer of the memory access program.
+fn memory_regions(count: usize, data: &[u32]) -> Vec<(u64, u64)> {
+    let mut regions = Vec::with_capacity(count);
+    if count > 0 {
+        let dummy = (DUMMY_REGION.as_ptr() as u64, DUMMY_REGION.len() as u64);
+        regions.resize(count - 1, dummy);
+        regions.push((data.as_ptr() as u64, core::mem::size_of_val(data) as u64));
+    }
+    regions
+}
+
+/// The memory the program may write, the Context or the region buffer the
+/// mbuff points to.
+#[cfg(all(feature = "compare-mem", feature = "libud"))]
+fn written_memory<'m>(mem: &'m mut [u8], _region_data: &'m mut [u32; REGION_WORDS]) -> &'m mut [u8] {
+    mem
+}
+
+#[cfg(all(feature = "compare-mem", not(feature = "libud")))]
+fn written_memory<'m>(_mem: &'m mut [u8], region_data: &'m mut [u32; REGION_WORDS]) -> &'m mut [u8] {
+    unsafe { core::slice::from_raw_parts_mut(region_data.as_mut_ptr() as *mut u8, core::mem::size_of_val(region_data)) }
+}
+
+/// Loads and executes the program once. Fails if the heap doesn't fit the
+/// VM or, with the JIT, its buffers, and with signed-programs if the
+/// signature doesn't match.
+fn run_iteration(program: &PreparedProgram) -> Result<Measurement, BenchError> {
+    let micro_sec = Clock::usec();
+    let prog = program.bytes;
+    let config = program.config;
+
+    #[cfg(feature = "reset-peripherals")]
+    infra::peripherals::reset(&config.reset_peripherals);
 
-                #[cfg(feature = "jit")]
+    #[cfg(feature = "deterministic-helpers")]
+    middleware::deterministic::reset();
+
+    // The signature is checked before the VM is constructed, as an
+    // installation would, but reported apart from the load time.
+    #[cfg(feature = "signed-programs")]
+    let sig_verify_us = {
+        let signature = infra::signing::signature_of(prog).ok_or(BenchError::InvalidSignature)?;
+        let mut valid = false;
+        let duration = micro_sec
+            .time(|| valid = infra::signing::verify(prog, signature))
+            .expect("failed to measure signature verification time")
+            .us();
+        if !valid {
+            return Err(BenchError::InvalidSignature);
+        }
+        duration
+    };
+    #[cfg(not(feature = "signed-programs"))]
+    let sig_verify_us = 0;
+
+    // Like the signature, the wrapped program is validated before the load
+    #[cfg(feature = "unwrap")]
+    let unwrap_us = {
+        let mut valid = false;
+        let duration = micro_sec
+            .time(|| valid = infra::unwrap::validate(prog))
+            .expect("failed to measure unwrap time")
+            .us();
+        if !valid {
+            return Err(BenchError::InvalidManifest);
+        }
+        duration
+    };
+
+    // rbpf aborts if an allocation fails, check that the VM fits beforehand.
+    // The check is outside of the timed load on purpose.
+    allocator::probe(allocator::VM_HEAP)?;
+    // Allocations of the JIT which fail inside the timed load
+    #[cfg(feature = "jit")]
+    let mut out_of_memory: Option<allocator::OutOfMemory> = None;
+
+    let mut vm: Option<Vm> = None;
+    #[cfg_attr(feature = "jit", allow(unused_variables))]
+    let kind = infra::vm::current();
+
+    #[cfg(feature = "jit")]
+    let mut jitted_fn: Option<unsafe fn(*mut u8, usize, *mut u8, usize) -> u32> = None;
+    #[cfg(feature = "jit")]
+    let mut jit_finalize_duration: u32 = 0;
+
+    #[cfg(feature = "seeds")]
+    let seed = infra::seeds::next(&config.seeds);
+    #[cfg(all(feature = "libud", not(feature = "seeds")))]
+    let mut ctx = Guarded::new(Context::new());
+    #[cfg(feature = "seeds")]
+    let mut ctx = Guarded::new(infra::seeds::context(seed));
+
+    // The native result of the input of this iteration, computed before the
+    // program changes the Context
+    #[cfg(feature = "reference")]
+    let native = infra::reference::expected_of(ctx.inner().clone());
+
+    #[cfg(feature = "libud")]
+    let mem = ctx.bytes_mut();
+
+    // With REGION_COUNT the program gets the address of a buffer which is only
+    // covered by the last of the allowed memory regions, see
+    // ebpf/memory_access.c. The address is written in the ARG_ENDIAN byte
+    // order.
+    let region_count = config.region_count;
+    // The VFS helpers read into the region buffer, see ebpf/vfs_read.c
+    #[cfg(feature = "vfs-helpers")]
+    let region_count = region_count.max(1);
+    #[cfg_attr(feature = "libud", allow(unused_mut))]
+    let mut region_data = Guarded::new([0u32; REGION_WORDS]);
+    #[cfg(not(feature = "libud"))]
+    let mut region_context = config.arg_endian.encode_u64(region_data.inner_mut().as_mut_ptr() as u64);
+    #[cfg_attr(feature = "jit", allow(unused_variables))]
+    let allowed_memory_regions = memory_regions(region_count, region_data.inner());
+
+    #[cfg(not(feature = "libud"))]
+    let mem: &mut [u8] = if region_count > 0 {
+        &mut region_context
+    } else {
+        &mut [] // Default empty slice if not libud
+    };
+    #[cfg(any(not(feature = "jit"), feature = "compare-mem"))]
+    let mbuff_len = mem.len();
+    // The JITted code only gets the memory to compare with compare-mem
+    #[cfg(all(feature = "jit", not(feature = "compare-mem")))]
+    let mbuff_len = 0;
+
+    // Registration is timed separately to show what registering only the
+    // referenced helpers saves, it is still included in the load time.
+    let mut register_duration: u32 = 0;
+
+    #[cfg(feature = "helper-pattern")]
+    let register = middleware::registration::next(config.helper_pattern);
+    #[cfg(not(feature = "helper-pattern"))]
+    #[cfg_attr(feature = "jit", allow(unused_variables))]
+    let register = true;
+    #[cfg_attr(not(feature = "helper-pattern"), allow(unused_mut))]
+    #[cfg_attr(feature = "jit", allow(unused_variables))]
+    let mut verified = true;
+
+    let load_program_duration = micro_sec
+        .time(|| {
+            #[cfg(not(feature = "jit"))]
+            {
+                let loaded = infra::load::load_program(
+                    prog,
+                    rbpf::InterpreterVariant::FemtoContainersHeader,
+                    kind,
+                    &program.helpers,
+                    register,
+                )
+                    .expect("failed to load program");
+                register_duration = loaded.register_us;
+                #[cfg(not(feature = "helper-pattern"))]
+                assert!(loaded.verified, "program verification failed");
+                // Without helpers a program calling them is expected to fail
+                #[cfg(feature = "helper-pattern")]
                 {
-                    use crate::middleware::ALL_HELPERS;
+                    verified = loaded.verified;
+                    assert!(verified || !register, "program verification failed");
+                }
+                vm = Some(loaded.vm);
+            }
 
-                    let mut prog_vec = prog.to_vec();
+            #[cfg(feature = "jit")]
+            {
+                let mut prog_vec = match allocator::try_to_vec(prog) {
+                    Ok(prog_vec) => prog_vec,
+                    Err(oom) => {
+                        out_of_memory = Some(oom);
+                        return;
+                    }
+                };
 
-                    let mut helpers_map = BTreeMap::new();
-                    for h in ALL_HELPERS.iter() {
-                        helpers_map.insert(h.id as u32, h.function);
+                let mut helpers_map = BTreeMap::new();
+                register_duration = micro_sec
+                    .time(|| {
+                        for h in program.helpers.iter() {
+                            helpers_map.insert(h.id as u32, h.function);
+                        }
+                    })
+                    .expect("failed to measure register helpers time")
+                    .us();
+
+                // Allocate the aligned buffer
+                let mut jit_memory_buff = match unsafe { allocator::try_box_zeroed::<AlignedBuffer>() } {
+                    Ok(buffer) => buffer,
+                    Err(oom) => {
+                        out_of_memory = Some(oom);
+                        return;
                     }
+                };
 
-                    // Allocate the aligned buffer
-                    let mut jit_memory_buff = Box::new(AlignedBuffer([0; JIT_MEMORY_BUFF_SIZE]));
+                println!("JIT compiling...");
 
-                    println!("JIT compiling...");
+                #[cfg(feature = "jit-timeout")]
+                infra::jit_timeout::arm();
+                let jit = rbpf::JitMemory::new(&mut prog_vec, &mut jit_memory_buff.0, &helpers_map, false, false, rbpf::InterpreterVariant::RawObjectFile).expect("Failed jit compile");
+                #[cfg(feature = "jit-timeout")]
+                infra::jit_timeout::disarm();
 
-                    let jit = rbpf::JitMemory::new(&mut prog_vec, &mut jit_memory_buff.0, &helpers_map, false, false, rbpf::InterpreterVariant::RawObjectFile).expect("Failed jit compile");
+                let offset = jit.text_offset.clone();
 
-                    let offset = jit.text_offset.clone();
+                jit_finalize_duration = micro_sec
+                    .time(|| {
+                        jitted_fn = Some(rbpf::JitMemory::get_prog_from_slice(
+                                &jit_memory_buff.0,
+                                offset,
+                            ));
+                    })
+                    .expect("failed to measure jit finalize time")
+                    .us();
 
-                    jitted_fn = Some(rbpf::JitMemory::get_prog_from_slice(
-                            &jit_memory_buff.0,
-                            offset,
-                        ));
+                println!("JIT compilation done.");
+            }
+        })
+        .expect("failed to measure load program time");
+    let load_end_us = util::time::now_us();
 
-                    println!("JIT compilation done.");
-                }
-            })
-            .expect("failed to measure load program time");
+    #[cfg(feature = "jit")]
+    if let Some(oom) = out_of_memory {
+        return Err(oom.into());
+    }
 
-        print!("{};", load_program_duration.0);
+    // Load the raw object file variant right after the header variant, so
+    // that both loads happen under the same conditions.
+    #[cfg(feature = "header-cost")]
+    let mut raw_vm: Option<EbpfVmMbuff> = None;
 
-        let mut res = false;
+    #[cfg(feature = "header-cost")]
+    let raw_load_program_duration = micro_sec
+        .time(|| {
+            raw_vm = Some(
+                EbpfVmMbuff::new(Some(RAW_PROG), rbpf::InterpreterVariant::RawObjectFile)
+                    .expect("failed to load raw program"),
+            );
+            register_helpers(raw_vm.as_mut().unwrap(), &program.raw_helpers);
+            raw_vm.as_ref().unwrap().verify_loaded_program().expect("raw program verification failed");
+        })
+        .expect("failed to measure raw load program time");
 
-        let execution_duration = micro_sec
-            .time(|| {
-                #[cfg(not(feature = "jit"))]
-                {
-                    let vm = vm.unwrap();
-                    let allowed_memory_regions: Vec<(u64, u64)> = Vec::new();
+    // Correct executions, reduced into the correct column with REDUCE
+    let mut passed: u32 = 0;
+    let mut first_correct = false;
+    let mut calls: u32 = 0;
+    // Whether every execution returned the native result
+    #[cfg(feature = "reference")]
+    let mut matches_native = true;
+    // Executions rbpf returned an error for, whatever SUCCESS_MODE says
+    #[cfg(feature = "error-summary")]
+    let mut execution_failed = false;
+    #[cfg(feature = "header-cost")]
+    let mut exec_result: u64 = 0;
+
+    #[cfg(feature = "reverify")]
+    let checksum = util::checksum::fnv1a(prog);
+
+    #[cfg(feature = "vfs-helpers")]
+    {
+        let buffer = region_data.inner();
+        let buffer = unsafe { core::slice::from_raw_parts(buffer.as_ptr() as *const u8, core::mem::size_of_val(buffer)) };
+        middleware::vfs::reset(prog, buffer);
+    }
+
+    #[cfg(not(feature = "seeds"))]
+    let success = config.success_mode;
+    // The reference runs on the Context of the seed
+    #[cfg(feature = "seeds")]
+    let success = match config.success_mode {
+        config::SuccessMode::Expected(_) => config::SuccessMode::Expected(native),
+        mode => mode,
+    };
+
+    // With exec-batch the loaded program is executed several times within one
+    // measurement.
+    #[cfg(feature = "exec-batch")]
+    let batch = config.exec_batch;
+    #[cfg(not(feature = "exec-batch"))]
+    let batch = 1;
 
-                    res = vm
-                        .execute_program(mem, &[], allowed_memory_regions)
-                        .expect("programm execution failed")
-                        == 1;
+    // An inconclusive analysis may hide helper calls
+    #[cfg(all(feature = "helper-cold", not(feature = "jit")))]
+    let calls_helpers = referenced_helper_ids(prog, ProgramFormat::FemtoContainersHeader).map_or(true, |ids| !ids.is_empty());
+    #[cfg(all(feature = "helper-cold", feature = "jit"))]
+    let calls_helpers = referenced_helper_ids(prog, ProgramFormat::RawObjectFile).map_or(true, |ids| !ids.is_empty());
+    #[cfg(all(feature = "helper-cold", not(feature = "jit")))]
+    let second_regions = allowed_memory_regions.clone();
 
+    #[cfg(feature = "compare-mem")]
+    let initial_mem = infra::mem_compare::snapshot(written_memory(mem, region_data.inner_mut()));
+
+    // The results are checked after the execution measurement, the check
+    // isn't part of it. Allocated up front, pushing doesn't allocate then.
+    let mut outcomes: Vec<Result<u64, rbpf::Error>> = Vec::with_capacity(batch as usize);
+    // Every execution takes the regions, only the executions before the
+    // last one need a copy, made up front as well. The single execution
+    // stays unchanged.
+    #[cfg(not(feature = "jit"))]
+    let mut region_copies: Vec<Vec<(u64, u64)>> = (1..batch).map(|_| allowed_memory_regions.clone()).collect();
+
+    // Last thing before the measurement, nothing else evicts the memory
+    #[cfg(feature = "prewarm-mbuff")]
+    let mbuff_prewarmed = infra::prewarm::due(config.mbuff_prewarm_alternate);
+    #[cfg(feature = "prewarm-mbuff")]
+    if mbuff_prewarmed {
+        infra::prewarm::touch(mem);
+        infra::prewarm::touch(region_data.inner());
+    }
+
+    #[cfg(feature = "ns-precision")]
+    let execution_start_ticks = util::time::ns::ticks();
+
+    #[cfg(feature = "pmu")]
+    infra::pmu::start();
+
+    let execution_start_us = util::time::now_us();
+    // The raw load of header-cost is measured on its own, it isn't part of
+    // the gap
+    #[cfg(feature = "header-cost")]
+    let gap_us = execution_start_us
+        .wrapping_sub(load_end_us)
+        .saturating_sub(raw_load_program_duration.us());
+    #[cfg(not(feature = "header-cost"))]
+    let gap_us = execution_start_us.wrapping_sub(load_end_us);
+
+    let execution_duration = micro_sec
+        .time(|| {
+            #[cfg(not(feature = "jit"))]
+            {
+                if verified {
+                    let vm = vm.as_ref().unwrap();
+                    let mut execute = |regions: Vec<(u64, u64)>| {
+                        outcomes.push(vm.execute_program(mem, regions));
+                    };
+                    for regions in region_copies.drain(..) {
+                        execute(regions);
+                    }
+                    execute(allowed_memory_regions);
                 }
-                
-                #[cfg(feature = "jit")]
-                {
-                    println!("Executing JITted code");
-                    // Sleep is needed sometimes, because when the execution fails no output is displayed otherwise.
-                    micro_sec.sleep(Ticks::from_duration(core::time::Duration::from_secs(3)).
-                        expect("5 would only overflow a nanosecond timer"));
+            }
 
-                    res = unsafe {
-                        jitted_fn.unwrap()(0 as *mut u8, 0, 0 as *mut u8, 0)
-                    } == 1;
+            #[cfg(feature = "jit")]
+            {
+                println!("Executing JITted code");
+                // Sleep is needed sometimes, because when the execution fails no output is displayed otherwise.
+                micro_sec.sleep(Ticks::from_duration(core::time::Duration::from_secs(3)).
+                    expect("5 would only overflow a nanosecond timer"));
 
-                    println!("JITted code execution done.");
+                for _ in 0..batch {
+                    #[cfg(not(feature = "compare-mem"))]
+                    let result = unsafe {
+                        jitted_fn.unwrap()(0 as *mut u8, 0, 0 as *mut u8, 0)
+                    };
+                    // The memory compared with the interpreter's, passed
+                    // like the interpreter passes it without an mbuff
+                    #[cfg(feature = "compare-mem")]
+                    let result = unsafe {
+                        jitted_fn.unwrap()(0 as *mut u8, 0, mem.as_mut_ptr(), mem.len())
+                    };
+                    // Failures of the JITted code can't be detected
+                    outcomes.push(Ok(result as u64));
                 }
-            })
-            .expect("failed to measure execution time");
 
-        print!("{};", execution_duration.0);
-        print!("{}\n", res.to_string());
+                println!("JITted code execution done.");
+            }
+        })
+        .expect("failed to measure execution time");
+
+    #[cfg(feature = "pmu")]
+    let pmu = infra::pmu::stop();
+
+    // Copied before any other execution touches the memory
+    #[cfg(feature = "compare-mem")]
+    let executed_mem = infra::mem_compare::snapshot(written_memory(mem, region_data.inner_mut()));
+
+    #[cfg_attr(feature = "check-cost", allow(unused_mut))]
+    let mut check = || {
+        for outcome in outcomes.drain(..) {
+            #[cfg(feature = "header-cost")]
+            {
+                exec_result = *outcome.as_ref().unwrap_or(&0);
+            }
+            #[cfg(feature = "reference")]
+            {
+                matches_native &= outcome.as_ref().map_or(false, |result| *result == native);
+            }
+            #[cfg(feature = "error-summary")]
+            {
+                execution_failed |= outcome.is_err();
+            }
+            let correct = success.is_success(outcome);
+            first_correct |= calls == 0 && correct;
+            passed += correct as u32;
+            calls += 1;
+        }
+    };
+    #[cfg(feature = "check-cost")]
+    let check_duration = micro_sec.time(check).expect("failed to measure check time").us();
+    #[cfg(not(feature = "check-cost"))]
+    check();
+
+    // Helpers keep their state across VMs, a slower first execution with
+    // the same VM points at state they initialize on the first call. Only
+    // programs calling helpers are executed again.
+    #[cfg(feature = "helper-cold")]
+    let second_execution_us = if verified && calls_helpers {
+        #[cfg(not(feature = "jit"))]
+        let second = micro_sec.time(|| {
+            let _ = vm.as_ref().unwrap().execute_program(mem, second_regions);
+        });
+        #[cfg(feature = "jit")]
+        let second = micro_sec.time(|| unsafe {
+            jitted_fn.unwrap()(0 as *mut u8, 0, 0 as *mut u8, 0);
+        });
+        Some(second.expect("failed to measure second execution time").us())
+    } else {
+        None
+    };
+
+    #[cfg(feature = "ns-precision")]
+    let execution_time_ns =
+        util::time::ns::elapsed_ns(execution_start_ticks, util::time::ns::ticks(), execution_duration.us());
+
+    // The raw variant, or the interpreter, starts from the same memory as
+    // the measured execution
+    #[cfg(feature = "compare-mem")]
+    infra::mem_compare::restore(written_memory(mem, region_data.inner_mut()), &initial_mem);
+
+    // The interpreter runs the raw object file the JIT compiled, outside of
+    // any timed region. Only the memory it leaves behind matters here.
+    #[cfg(all(feature = "compare-mem", feature = "jit"))]
+    {
+        let mut interpreter = EbpfVmMbuff::new(Some(prog), rbpf::InterpreterVariant::RawObjectFile)
+            .expect("failed to load the program into the interpreter");
+        register_helpers(&mut interpreter, &program.helpers);
+        let _ = interpreter.execute_program(mem, &[], allowed_memory_regions);
     }
 
-    println!("=== Benchmark End ===");
+    // The raw variant is executed outside of any timed region, only its
+    // result matters here.
+    #[cfg(feature = "header-cost")]
+    let raw_result = raw_vm
+        .unwrap()
+        .execute_program(mem, &[], Vec::new())
+        .expect("raw programm execution failed");
+
+    #[cfg(feature = "compare-mem")]
+    let mem_mismatch = infra::mem_compare::first_difference(written_memory(mem, region_data.inner_mut()), &executed_mem);
+
+    // An out of bounds write into the text region would either break the
+    // program so that it no longer verifies or at least change its bytes.
+    #[cfg(feature = "reverify")]
+    let corrupted = (verified && !vm.as_ref().unwrap().verify_loaded_program()) || util::checksum::fnv1a(prog) != checksum;
+
+    // Checked after the timed regions, an overwritten canary means the
+    // program (or the VM) wrote outside of the memory it was given.
+    #[cfg(feature = "libud")]
+    let context_damage = ctx.check().err().map(|damage| ("context", damage));
+    #[cfg(not(feature = "libud"))]
+    let context_damage = None;
+    let canary = context_damage.or_else(|| region_data.check().err().map(|damage| ("region", damage)));
+    // After the timed regions, the VM of the iteration is left alone
+    #[cfg(feature = "register-overhead")]
+    let (register_fresh_us, register_overhead_us) = infra::register_overhead::measure(prog, config.planned_iterations());
+    #[cfg(all(feature = "mbuff-guard", feature = "libud"))]
+    let guard_ok = ctx.guard_intact() && region_data.guard_intact();
+    #[cfg(all(feature = "mbuff-guard", not(feature = "libud")))]
+    let guard_ok = region_data.guard_intact();
+    // An execution that didn't happen (e.g. a program that failed to verify
+    // with helper-pattern) is never correct
+    let res = calls > 0 && config.reduce.reduce(first_correct, passed, calls);
+
+    Ok(Measurement {
+        load_program_us: load_program_duration.us(),
+        register_us: register_duration,
+        sig_verify_us,
+        mbuff_len,
+        gap_us,
+        execution_time_us: execution_duration.us(),
+        correct: res && canary.is_none(),
+        #[cfg(feature = "header-cost")]
+        raw_load_program_us: raw_load_program_duration.us(),
+        #[cfg(feature = "header-cost")]
+        results_match: raw_result == exec_result,
+        #[cfg(feature = "ns-precision")]
+        execution_time_ns,
+        #[cfg(feature = "reverify")]
+        corrupted,
+        #[cfg(feature = "helper-pattern")]
+        helpers_registered: register,
+        #[cfg(feature = "helper-pattern")]
+        helpers_registered_count: if register {
+            registered_helper_count(&program.helpers)
+        } else {
+            0
+        },
+        #[cfg(feature = "helper-pattern")]
+        verified,
+        #[cfg(feature = "exec-batch")]
+        per_call_ns: execution_duration.ns() / batch as u64,
+        #[cfg(feature = "canary-check")]
+        stack_ok: infra::stack::canary_intact(),
+        #[cfg(feature = "pmu")]
+        pmu,
+        #[cfg(feature = "trigger")]
+        execution_start_us,
+        #[cfg(feature = "trigger")]
+        wakeup_delay_us: 0,
+        #[cfg(feature = "jit")]
+        jit_finalize_us: jit_finalize_duration,
+        #[cfg(feature = "check-cost")]
+        check_us: check_duration,
+        #[cfg(feature = "compare-mem")]
+        mem_mismatch,
+        #[cfg(feature = "prewarm-mbuff")]
+        mbuff_prewarmed,
+        #[cfg(feature = "mbuff-guard")]
+        guard_ok,
+        #[cfg(feature = "unwrap")]
+        unwrap_us,
+        #[cfg(feature = "seeds")]
+        seed,
+        #[cfg(feature = "reference")]
+        matches_native: matches_native && calls > 0,
+        #[cfg(feature = "error-summary")]
+        execution_failed,
+        #[cfg(feature = "register-overhead")]
+        register_fresh_us,
+        #[cfg(feature = "register-overhead")]
+        register_overhead_us,
+        #[cfg(feature = "helper-cold")]
+        second_execution_us,
+        #[cfg(feature = "dump-context")]
+        context: infra::context_dump::capture(ctx.inner()),
+        canary,
+    })
 }
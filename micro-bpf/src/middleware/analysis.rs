@@ -0,0 +1,107 @@
+//! Static analysis of programs to find out which helpers they call, so that
+//! only those need to be registered with the VM.
+
+use alloc::vec::Vec;
+
+use super::helpers::HelperFunction;
+use super::ALL_HELPERS;
+use crate::infra::error::BenchError;
+use crate::util::elf;
+
+/// Encoding of the program bytes handed to the analysis.
+#[derive(Copy, Clone)]
+pub enum ProgramFormat {
+    /// Program prefixed with the Femto-Container header (benchmark.bin).
+    FemtoContainersHeader,
+    /// Raw ELF object file as produced by the compiler (benchmark.o).
+    RawObjectFile,
+}
+
+const INSN_SIZE: usize = 8;
+const CALL_OPCODE: u8 = 0x85;
+/// The load double word instruction spans two instruction slots.
+const LDDW_OPCODE: u8 = 0x18;
+/// Source register value marking a call to a local function instead of a helper.
+const PSEUDO_CALL: u8 = 1;
+/// Start of a Femto-Container header.
+pub const FC_MAGIC: &[u8] = b"rBPF";
+/// magic, version, flags, data_len, rodata_len, text_len, functions
+pub const FC_HEADER_SIZE: usize = 7 * 4;
+
+/// Returns the ids of all helpers called by the program, or None if the
+/// analysis is inconclusive (malformed program or calls which are only
+/// resolved by relocations at load time).
+pub fn referenced_helper_ids(prog: &[u8], format: ProgramFormat) -> Option<Vec<u32>> {
+    let mut ids = Vec::new();
+    match format {
+        ProgramFormat::FemtoContainersHeader => scan_calls(header_text(prog)?, &mut ids)?,
+        ProgramFormat::RawObjectFile => {
+            for section in elf::sections(prog)?.iter().filter(|s| s.is_executable()) {
+                scan_calls(section.data, &mut ids)?;
+            }
+        }
+    }
+    Some(ids)
+}
+
+/// Returns the helpers which need to be registered for the program, falling
+/// back to all helpers when the analysis is inconclusive. The VFS helpers are
+/// registered separately and not included.
+///
+/// Fails with the id of a helper the program calls that isn't implemented,
+/// this would otherwise only surface once the call is executed.
+pub fn referenced_helpers(prog: &[u8], format: ProgramFormat) -> Result<Vec<HelperFunction>, BenchError> {
+    let ids = match referenced_helper_ids(prog, format) {
+        Some(ids) => ids,
+        None => return Ok(ALL_HELPERS.to_vec()),
+    };
+    #[cfg(feature = "vfs-helpers")]
+    let ids: Vec<u32> = ids.into_iter().filter(|id| !super::vfs::is_vfs_helper(*id)).collect();
+    ids.into_iter()
+        .map(|id| {
+            ALL_HELPERS
+                .iter()
+                .find(|h| h.id as u32 == id)
+                .copied()
+                .ok_or(BenchError::UnimplementedHelper(id))
+        })
+        .collect()
+}
+
+/// Extracts the text section from a program with the Femto-Container header,
+/// None if the sections don't fit in the program.
+pub fn header_text(prog: &[u8]) -> Option<&[u8]> {
+    let field = |i: usize| {
+        prog.get(i * 4..i * 4 + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    };
+    let data_len = field(3)?;
+    let rodata_len = field(4)?;
+    let text_len = field(5)?;
+    let start = FC_HEADER_SIZE.checked_add(data_len)?.checked_add(rodata_len)?;
+    prog.get(start..start.checked_add(text_len)?)
+}
+
+/// Collects the immediate of all helper calls in an instruction stream.
+fn scan_calls(text: &[u8], ids: &mut Vec<u32>) -> Option<()> {
+    let mut offset = 0;
+    while offset + INSN_SIZE <= text.len() {
+        let insn = &text[offset..offset + INSN_SIZE];
+        match insn[0] {
+            CALL_OPCODE if insn[1] >> 4 != PSEUDO_CALL => {
+                let imm = i32::from_le_bytes([insn[4], insn[5], insn[6], insn[7]]);
+                // The compiler leaves -1 for calls that are patched by relocations.
+                if imm == -1 {
+                    return None;
+                }
+                if !ids.contains(&(imm as u32)) {
+                    ids.push(imm as u32);
+                }
+            }
+            LDDW_OPCODE => offset += INSN_SIZE,
+            _ => {}
+        }
+        offset += INSN_SIZE;
+    }
+    Some(())
+}
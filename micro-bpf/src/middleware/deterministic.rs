@@ -0,0 +1,43 @@
+//! Deterministic stand-ins for the helpers whose results depend on when they
+//! are called, so that repeated runs (and runs on different boards) execute
+//! the same instructions and return the same results. Affected are the time
+//! helpers only:
+//!
+//! * `bpf_now_ms` and `bpf_ztimer_now` read a virtual clock instead of ZTIMER.
+//!   It starts at 0 in every iteration and advances by `STEP_US` with every
+//!   call of either of them, so it is monotonic and the microsecond clock
+//!   moves between two calls as ebpf/selftest.c expects.
+//!
+//! No helper returns random numbers, the other helpers only depend on their
+//! arguments and the program's memory.
+
+use portable_atomic::{AtomicU32, Ordering};
+
+/// The replaced helpers, as reported with the metadata.
+pub const AFFECTED: &str = "bpf_now_ms,bpf_ztimer_now";
+
+/// Time passing with each call of a time helper.
+const STEP_US: u32 = 10;
+
+/// The virtual clock in microseconds.
+static CLOCK_US: AtomicU32 = AtomicU32::new(0);
+
+/// Restarts the clock, before each iteration.
+pub fn reset() {
+    CLOCK_US.store(0, Ordering::Relaxed);
+}
+
+/// The clock after advancing it by one step.
+fn tick() -> u32 {
+    CLOCK_US.fetch_add(STEP_US, Ordering::Relaxed).wrapping_add(STEP_US)
+}
+
+/// Stand-in for `bpf_now_ms`, the virtual clock in milliseconds.
+pub fn bpf_now_ms(_a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
+    (tick() / 1000) as u64
+}
+
+/// Stand-in for `bpf_ztimer_now`, the virtual clock in microseconds.
+pub fn bpf_ztimer_now(_a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
+    tick() as u64
+}
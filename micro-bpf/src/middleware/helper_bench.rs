@@ -0,0 +1,77 @@
+//! Cost of a single helper called directly from the harness, without a
+//! program around it. The helper is called `HELPER_BENCH_CALLS` times in a
+//! loop under one timer read, less the same loop calling a helper that does
+//! nothing, which leaves a per-call time finer than the microsecond timer.
+//!
+//! The arguments are fixed per helper so that it does real work on valid
+//! memory. The printing helpers are skipped, they would time the console.
+
+use alloc::format;
+use micro_bpf_common::HelperFunctionID as ID;
+use riot_wrappers::ztimer::Clock;
+
+use super::ALL_HELPERS;
+use crate::infra::config;
+use crate::output::ResultSink;
+use crate::util::time::Elapsed;
+
+/// Bytes copied by bpf_memcpy.
+const COPY_BYTES: usize = 32;
+const STRING: &[u8] = b"helper benchmark\0";
+
+fn noop(_a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
+    0
+}
+
+/// Time of `calls` calls of `function` with `args`.
+fn loop_us(function: fn(u64, u64, u64, u64, u64) -> u64, args: [u64; 5], calls: u32) -> u32 {
+    // Called through the pointer as the VM does, black_box keeps the
+    // compiler from inlining the helper or dropping the calls.
+    let function = core::hint::black_box(function);
+    Clock::usec()
+        .time(|| {
+            for _ in 0..calls {
+                core::hint::black_box(function(args[0], args[1], args[2], args[3], args[4]));
+            }
+        })
+        .expect("failed to measure helper time")
+        .us()
+}
+
+/// Benchmarks the helper `HELPER_BENCH_ID` and reports the time per call.
+pub fn run(sink: &mut dyn ResultSink) {
+    let id = config::helper_bench_id();
+    let calls = config::helper_bench_calls().max(1);
+    let helper = match ALL_HELPERS.iter().find(|helper| helper.id as u32 == id) {
+        Some(helper) => helper,
+        None => {
+            sink.comment(&format!("helper_bench=skipped no helper {:#04x} is implemented", id));
+            return;
+        }
+    };
+
+    let mut buffer = [0u8; 2 * COPY_BYTES];
+    let (destination, source) = buffer.split_at_mut(COPY_BYTES);
+    let args = match helper.id {
+        ID::BPF_MEMCPY_IDX => [destination.as_mut_ptr() as u64, source.as_ptr() as u64, COPY_BYTES as u64, 0, 0],
+        ID::BPF_STRLEN_IDX => [STRING.as_ptr() as u64, 0, 0, 0, 0],
+        ID::BPF_NOW_MS_IDX | ID::BPF_ZTIMER_NOW_IDX => [0; 5],
+        _ => {
+            sink.comment(&format!("helper_bench=skipped helper {:#04x} prints on every call", id));
+            return;
+        }
+    };
+
+    let total_us = loop_us(helper.function, args, calls);
+    let loop_overhead_us = loop_us(noop, args, calls);
+    let net_ps = (total_us.saturating_sub(loop_overhead_us) as u64 * 1_000_000) / calls as u64;
+    sink.comment(&format!(
+        "helper_bench helper={:#04x} calls={} total_us={} loop_us={} per_call_ns={}.{:03}",
+        id,
+        calls,
+        total_us,
+        loop_overhead_us,
+        net_ps / 1000,
+        net_ps % 1000
+    ));
+}
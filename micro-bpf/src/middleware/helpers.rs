@@ -118,8 +118,8 @@ pub fn register_all(vm: &mut impl AcceptingHelpers) {
 }
 
 #[allow(dead_code)]
-pub fn register_helpers(vm: &mut impl AcceptingHelpers, helpers: Vec<HelperFunction>) {
+pub fn register_helpers(vm: &mut impl AcceptingHelpers, helpers: &[HelperFunction]) {
     for helper in helpers {
-        vm.register_helper(helper);
+        vm.register_helper(*helper);
     }
 }
@@ -1,4 +1,23 @@
+pub mod analysis;
+#[cfg(feature = "deterministic-helpers")]
+pub mod deterministic;
+#[cfg(feature = "helper-bench")]
+pub mod helper_bench;
 pub mod helpers;
+#[cfg(feature = "helper-profile")]
+pub mod profile;
+#[cfg(feature = "helper-pattern")]
+pub mod registration;
 pub mod riot_middleware;
+#[cfg(feature = "selftest")]
+pub mod selftest;
+#[cfg(feature = "verify-profile")]
+pub mod verify_profile;
+#[cfg(feature = "verify-reject")]
+pub mod verify_reject;
+#[cfg(feature = "verify-registration")]
+pub mod verify_registration;
+#[cfg(feature = "vfs-helpers")]
+pub mod vfs;
 
 pub use riot_middleware::*;
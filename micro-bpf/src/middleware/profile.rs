@@ -0,0 +1,83 @@
+//! Per-helper timing shims. With the helper-profile feature the helpers are
+//! registered through shims which count the calls of each helper and the time
+//! spent inside of it. The time of the shims themselves is part of the
+//! execution time of the program.
+
+use alloc::vec::Vec;
+use micro_bpf_common::HelperFunctionID;
+use portable_atomic::{AtomicU32, Ordering};
+
+use super::helpers::HelperFunction;
+use super::ALL_HELPERS;
+use crate::util::time::now_us;
+
+/// Number of shims available, one per entry of ALL_HELPERS.
+const MAX_HELPERS: usize = 8;
+const _: () = assert!(ALL_HELPERS.len() <= MAX_HELPERS, "add more helper shims");
+
+const ZERO: AtomicU32 = AtomicU32::new(0);
+static CALLS: [AtomicU32; MAX_HELPERS] = [ZERO; MAX_HELPERS];
+static TIME_US: [AtomicU32; MAX_HELPERS] = [ZERO; MAX_HELPERS];
+
+/// The VM only stores plain function pointers, so there is one shim per index
+/// into ALL_HELPERS which knows which helper it forwards to.
+macro_rules! shims {
+    ( $( $index:literal ),* ) => {
+        [ $( {
+            fn shim(a1: u64, a2: u64, a3: u64, a4: u64, a5: u64) -> u64 {
+                call($index, a1, a2, a3, a4, a5)
+            }
+            shim as fn(u64, u64, u64, u64, u64) -> u64
+        } ),* ]
+    };
+}
+
+const SHIMS: [fn(u64, u64, u64, u64, u64) -> u64; MAX_HELPERS] = shims!(0, 1, 2, 3, 4, 5, 6, 7);
+
+fn call(index: usize, a1: u64, a2: u64, a3: u64, a4: u64, a5: u64) -> u64 {
+    let start = now_us();
+    let result = (ALL_HELPERS[index].function)(a1, a2, a3, a4, a5);
+    TIME_US[index].fetch_add(now_us().wrapping_sub(start), Ordering::Relaxed);
+    CALLS[index].fetch_add(1, Ordering::Relaxed);
+    result
+}
+
+/// Replaces the implementations of the helpers with their profiling shims.
+pub fn instrument(helpers: Vec<HelperFunction>) -> Vec<HelperFunction> {
+    helpers
+        .into_iter()
+        .map(|helper| {
+            let index = ALL_HELPERS.iter().position(|h| h.id == helper.id).unwrap();
+            HelperFunction::new(helper.id, SHIMS[index])
+        })
+        .collect()
+}
+
+/// Clears all counters, e.g. before the recorded iterations of a program.
+pub fn reset() {
+    for index in 0..MAX_HELPERS {
+        CALLS[index].store(0, Ordering::Relaxed);
+        TIME_US[index].store(0, Ordering::Relaxed);
+    }
+}
+
+/// Calls and time of a single helper since the last reset.
+pub struct HelperProfile {
+    pub id: HelperFunctionID,
+    pub calls: u32,
+    pub total_us: u32,
+}
+
+/// Returns the profile of every helper that was called since the last reset.
+pub fn snapshot() -> Vec<HelperProfile> {
+    ALL_HELPERS
+        .iter()
+        .enumerate()
+        .map(|(index, helper)| HelperProfile {
+            id: helper.id,
+            calls: CALLS[index].load(Ordering::Relaxed),
+            total_us: TIME_US[index].load(Ordering::Relaxed),
+        })
+        .filter(|profile| profile.calls > 0)
+        .collect()
+}
@@ -0,0 +1,19 @@
+//! Chooses per iteration whether the helpers are registered, following
+//! `HELPER_PATTERN`: a string of 1 (register) and 0 (skip) which is repeated
+//! over the iterations, e.g. 10 alternates.
+
+use portable_atomic::{AtomicUsize, Ordering};
+
+static ITERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Restarts the pattern, done before the recorded iterations of a program.
+pub fn reset() {
+    ITERATION.store(0, Ordering::Relaxed);
+}
+
+/// Whether the helpers are registered in the next iteration, following
+/// `Config::helper_pattern`.
+pub fn next(pattern: &[u8]) -> bool {
+    let iteration = ITERATION.fetch_add(1, Ordering::Relaxed);
+    pattern[iteration % pattern.len()] == b'1'
+}
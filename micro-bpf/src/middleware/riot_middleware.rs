@@ -28,16 +28,23 @@ type HF = HelperFunction;
 
 /// List of all helpers together with their corresponding numbers (used
 /// directly as function pointers in the compiled eBPF bytecode).
-pub const ALL_HELPERS: [HelperFunction; 2] = [
+pub const ALL_HELPERS: [HelperFunction; 6] = [
     HF::new(ID::BPF_DEBUG_PRINT_IDX, bpf_print_debug),
     HF::new(ID::BPF_PRINTF_IDX, bpf_printf),
+    HF::new(ID::BPF_MEMCPY_IDX, bpf_memcpy),
+    #[cfg(not(feature = "deterministic-helpers"))]
+    HF::new(ID::BPF_NOW_MS_IDX, bpf_now_ms),
+    #[cfg(not(feature = "deterministic-helpers"))]
+    HF::new(ID::BPF_ZTIMER_NOW_IDX, bpf_ztimer_now),
+    #[cfg(feature = "deterministic-helpers")]
+    HF::new(ID::BPF_NOW_MS_IDX, super::deterministic::bpf_now_ms),
+    #[cfg(feature = "deterministic-helpers")]
+    HF::new(ID::BPF_ZTIMER_NOW_IDX, super::deterministic::bpf_ztimer_now),
+    HF::new(ID::BPF_STRLEN_IDX, bpf_strlen),
     // HF::new(ID::BPF_STORE_LOCAL_IDX, bpf_store_local),
     // HF::new(ID::BPF_STORE_GLOBAL_IDX, bpf_store_global),
     // HF::new(ID::BPF_FETCH_LOCAL_IDX, bpf_fetch_local),
     // HF::new(ID::BPF_FETCH_GLOBAL_IDX, bpf_fetch_global),
-    // HF::new(ID::BPF_MEMCPY_IDX, bpf_memcpy),
-    // HF::new(ID::BPF_NOW_MS_IDX, bpf_now_ms),
-    // HF::new(ID::BPF_ZTIMER_NOW_IDX, bpf_ztimer_now),
     // HF::new(ID::BPF_PERIODIC_WAKEUP_IDX, bpf_periodic_wakeup),
     // HF::new(ID::BPF_SAUL_REG_FIND_NTH_IDX, bpf_saul_reg_find_nth),
     // HF::new(ID::BPF_SAUL_REG_FIND_TYPE_IDX, bpf_saul_reg_find_type),
@@ -48,7 +55,6 @@ pub const ALL_HELPERS: [HelperFunction; 2] = [
     // HF::new(ID::BPF_COAP_OPT_FINISH_IDX, bpf_coap_opt_finish),
     // HF::new(ID::BPF_COAP_ADD_FORMAT_IDX, bpf_coap_add_format),
     // HF::new(ID::BPF_COAP_GET_PDU_IDX, bpf_coap_get_pdu),
-    // HF::new(ID::BPF_STRLEN_IDX, bpf_strlen),
     // HF::new(ID::BPF_FMT_S16_DFP_IDX, bpf_fmt_s16_dfp),
     // HF::new(ID::BPF_FMT_U32_DEC_IDX, bpf_fmt_u32_dec),
     // HF::new(ID::BPF_GPIO_READ_INPUT, bpf_gpio_read_input),
@@ -142,16 +148,15 @@ pub fn bpf_print_debug(a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
 //     unsafe { bpf_store_fetch_global(key as u32, value as *mut u32) as u64 }
 // }
 
-// /* Standard library functions */
-// pub fn bpf_memcpy(dest_p: u64, src_p: u64, size: u64, _a4: u64, _a5: u64) -> u64 {
-//     let dest: *mut riot_sys::libc::c_void = dest_p as *mut riot_sys::libc::c_void;
-//     let src: *const riot_sys::libc::c_void = src_p as *const riot_sys::libc::c_void;
-//     let size = size as u32;
-//     debug!("Copying {} bytes from {:x} to {:x}", size, src_p, dest_p);
-//     unsafe {
-//         return riot_sys::memcpy(dest, src, size) as u64;
-//     }
-// }
+/* Standard library functions */
+pub fn bpf_memcpy(dest_p: u64, src_p: u64, size: u64, _a4: u64, _a5: u64) -> u64 {
+    let dest: *mut riot_sys::libc::c_void = dest_p as *mut riot_sys::libc::c_void;
+    let src: *const riot_sys::libc::c_void = src_p as *const riot_sys::libc::c_void;
+    debug!("Copying {} bytes from {:x} to {:x}", size, src_p, dest_p);
+    unsafe {
+        return riot_sys::memcpy(dest, src, size as _) as u64;
+    }
+}
 
 // /* Saul functions - implementation */
 // /// Find a SAUL device by its position in the registry. It returns a pointer to
@@ -260,23 +265,23 @@ pub fn bpf_print_debug(a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
 //     return 0;
 // }
 
-// /// Returns the current time in milliseconds as measured by RIOT's ZTIMER.
-// pub fn bpf_now_ms(_a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
-//     let clock = unsafe { riot_sys::ZTIMER_MSEC as *mut riot_sys::inline::ztimer_clock_t };
-//     let now: u32 = unsafe { riot_sys::inline::ztimer_now(clock) };
-//     now as u64
-// }
+/// Returns the current time in milliseconds as measured by RIOT's ZTIMER.
+pub fn bpf_now_ms(_a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
+    let clock = unsafe { riot_sys::ZTIMER_MSEC as *mut riot_sys::inline::ztimer_clock_t };
+    let now: u32 = unsafe { riot_sys::inline::ztimer_now(clock) };
+    now as u64
+}
 
-// /// Returns the current time in microseconds as measured by RIOT's ZTIMER.
-// pub fn bpf_ztimer_now(_a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
-//     let now: u32 = unsafe {
-//         // An explicit cast into *mut riot_sys::inline::ztimer_clock_t is needed here
-//         // because the type of riot_sys::ZTIMER_USEC is riot_sys::ztimer_clock_t
-//         // and the compiler complains about the mismatching type.
-//         riot_sys::inline::ztimer_now(riot_sys::ZTIMER_USEC as *mut riot_sys::inline::ztimer_clock_t)
-//     };
-//     now as u64
-// }
+/// Returns the current time in microseconds as measured by RIOT's ZTIMER.
+pub fn bpf_ztimer_now(_a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
+    let now: u32 = unsafe {
+        // An explicit cast into *mut riot_sys::inline::ztimer_clock_t is needed here
+        // because the type of riot_sys::ZTIMER_USEC is riot_sys::ztimer_clock_t
+        // and the compiler complains about the mismatching type.
+        riot_sys::inline::ztimer_now(riot_sys::ZTIMER_USEC as *mut riot_sys::inline::ztimer_clock_t)
+    };
+    now as u64
+}
 
 // /// Suspend the calling thread until the time (last_wakeup + period)
 // pub fn bpf_periodic_wakeup(last_wakeup: u64, period: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
@@ -287,14 +292,14 @@ pub fn bpf_print_debug(a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
 //     return 0;
 // }
 
-// /* Format and string functions - implementation */
-// pub fn bpf_strlen(str_ptr: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
-//     let str_ptr = str_ptr as *const i8;
-//     unsafe {
-//         let c_str = CStr::from_ptr(str_ptr);
-//         return c_str.to_bytes().len() as u64;
-//     }
-// }
+/* Format and string functions - implementation */
+pub fn bpf_strlen(str_ptr: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
+    let str_ptr = str_ptr as *const i8;
+    unsafe {
+        let c_str = CStr::from_ptr(str_ptr);
+        return c_str.to_bytes().len() as u64;
+    }
+}
 
 // /// Convert 16-bit fixed point number to a decimal string.
 // /// Returns the length of the resulting string.
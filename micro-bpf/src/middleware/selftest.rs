@@ -0,0 +1,49 @@
+//! Self-test of the helper set, run once before the benchmark. A built-in
+//! program (ebpf/selftest.c, rebuilt by `make selftest.bin`) calls every helper
+//! in ALL_HELPERS and returns a bitmask with bit i set if `ALL_HELPERS[i]`
+//! behaved. The helpers are registered without the profiling shims, so the
+//! self-test doesn't show up in the helper profile.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use rbpf::EbpfVmMbuff;
+
+use super::helpers::register_helpers;
+use super::ALL_HELPERS;
+use crate::output::ResultSink;
+
+static PROGRAM: &[u8] = include_bytes!(env!("SELFTEST_BIN_PATH"));
+
+/// Runs the self-test and reports the result. Returns false if a helper
+/// failed, the benchmark must not start then.
+pub fn run(sink: &mut dyn ResultSink) -> bool {
+    let mut vm = EbpfVmMbuff::new(Some(PROGRAM), rbpf::InterpreterVariant::FemtoContainersHeader)
+        .expect("failed to load the self-test program");
+    register_helpers(&mut vm, &ALL_HELPERS);
+    if vm.verify_loaded_program().is_err() {
+        sink.metadata("selftest", "failed, the program doesn't verify with the registered helpers");
+        return false;
+    }
+    let mask = match vm.execute_program(&[], &[], Vec::new()) {
+        Ok(mask) => mask,
+        Err(_) => {
+            sink.metadata("selftest", "failed, the program didn't run to completion");
+            return false;
+        }
+    };
+
+    let failed: Vec<String> = ALL_HELPERS
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| mask & (1 << index) == 0)
+        .map(|(_, helper)| format!("{:#04x}", helper.id as u8))
+        .collect();
+    if failed.is_empty() {
+        sink.metadata("selftest", "ok");
+        true
+    } else {
+        sink.metadata("selftest", &format!("failed helpers={}", failed.join(",")));
+        false
+    }
+}
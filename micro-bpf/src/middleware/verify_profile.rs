@@ -0,0 +1,120 @@
+//! Verification cost per instruction class. rbpf's verifier has no hooks to
+//! time its checks per instruction, so the cost is approximated with
+//! synthetic programs which consist of a single instruction class each. The
+//! time to verify them, less the time for a program of plain ALU instructions
+//! of the same length, is the extra cost of checking that class.
+
+use alloc::format;
+use alloc::vec::Vec;
+use rbpf::EbpfVmMbuff;
+use riot_wrappers::ztimer::Clock;
+
+use super::helpers::register_helpers;
+use super::ALL_HELPERS;
+use crate::output::ResultSink;
+use crate::util::stats::Accumulator;
+use crate::util::time::Elapsed;
+
+/// Instructions of the class per synthetic program.
+const INSNS: usize = 256;
+/// Verifications per class, the mean is reported.
+const ROUNDS: usize = 16;
+/// magic, version, flags, data_len, rodata_len, text_len, functions
+const FC_HEADER_SIZE: usize = 7 * 4;
+
+/// (name, instructions repeated to fill the program)
+const CLASSES: [(&str, &[[u8; 8]]); 5] = [
+    // mov r1, 1
+    ("alu", &[[0xb7, 0x01, 0, 0, 1, 0, 0, 0]]),
+    // jeq r1, 0, +0
+    ("jump", &[[0x15, 0x01, 0, 0, 0, 0, 0, 0]]),
+    // stxdw [r10-8], r1; ldxdw r1, [r10-8]
+    ("memory", &[[0x7b, 0x1a, 0xf8, 0xff, 0, 0, 0, 0], [0x79, 0xa1, 0xf8, 0xff, 0, 0, 0, 0]]),
+    // lddw r1, 1 spans two slots
+    ("lddw", &[[0x18, 0x01, 0, 0, 1, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0]]),
+    // call of the first helper, patched in by `synthesize`
+    ("call", &[[0x85, 0, 0, 0, 0, 0, 0, 0]]),
+];
+
+/// Builds a program of `INSNS` instructions from `pattern`, followed by
+/// `mov r0, 0; exit`. The header is copied from `template` so that it
+/// matches what the VM expects, with only the text section left.
+fn synthesize(template: &[u8], pattern: &[[u8; 8]]) -> Vec<u8> {
+    let mut text = Vec::with_capacity((INSNS + 2) * 8);
+    while text.len() < INSNS * 8 {
+        for insn in pattern {
+            let mut insn = *insn;
+            if insn[0] == 0x85 {
+                insn[4..8].copy_from_slice(&(ALL_HELPERS[0].id as u32).to_le_bytes());
+            }
+            text.extend_from_slice(&insn);
+        }
+    }
+    text.extend_from_slice(&[0xb7, 0, 0, 0, 0, 0, 0, 0]);
+    text.extend_from_slice(&[0x95, 0, 0, 0, 0, 0, 0, 0]);
+
+    let mut prog = Vec::with_capacity(FC_HEADER_SIZE + text.len());
+    prog.extend_from_slice(&template[..12]);
+    // No data and rodata, the text and no functions
+    for field in [0, 0, text.len() as u32, 0].iter() {
+        prog.extend_from_slice(&field.to_le_bytes());
+    }
+    prog.extend_from_slice(&text);
+    prog
+}
+
+/// Mean time to verify the program, `None` if it doesn't verify.
+fn verify_us(prog: &[u8]) -> Option<u32> {
+    let micro_sec = Clock::usec();
+    let mut times = Accumulator::new();
+    for _ in 0..ROUNDS {
+        let mut vm = EbpfVmMbuff::new(Some(prog), rbpf::InterpreterVariant::FemtoContainersHeader).ok()?;
+        register_helpers(&mut vm, &ALL_HELPERS);
+        let mut verified = false;
+        let duration = micro_sec
+            .time(|| verified = vm.verify_loaded_program().is_ok())
+            .expect("failed to measure verification time")
+            .us();
+        if !verified {
+            return None;
+        }
+        times.add(duration);
+    }
+    times.mean()
+}
+
+/// Reports the verification cost of each class. `template` is a program with
+/// the Femto-Container header whose header fields are reused.
+pub fn run(sink: &mut dyn ResultSink, template: &[u8]) {
+    if template.len() < FC_HEADER_SIZE {
+        sink.comment("verify_profile=skipped the program has no Femto-Container header to copy");
+        return;
+    }
+    sink.comment(&format!(
+        "verify_profile method=synthetic insns={} rounds={}, excess is the time above the alu program",
+        INSNS, ROUNDS
+    ));
+    // The alu class comes first and is the reference of the others
+    let mut alu_us = None;
+    for (name, pattern) in CLASSES.iter() {
+        match verify_us(&synthesize(template, pattern)) {
+            Some(us) => {
+                if *name == "alu" {
+                    alu_us = Some(us);
+                }
+                let excess = match alu_us {
+                    Some(alu) => format!("{}", (us as i64 - alu as i64) * 1000 / INSNS as i64),
+                    None => "na".into(),
+                };
+                sink.comment(&format!(
+                    "verify_profile class={} verify_us={} ns_per_insn={} excess_ns_per_insn={}",
+                    name,
+                    us,
+                    us as u64 * 1000 / INSNS as u64,
+                    excess
+                ));
+            }
+            None => sink.comment(&format!("verify_profile class={} rejected by the verifier", name)),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! What registering the helpers costs the verifier. A program that calls no
+//! helpers verifies with all of them registered and with none, the
+//! difference is the verifier's bookkeeping for the registered helpers.
+//! Programs calling helpers don't verify without them and are skipped.
+
+use alloc::format;
+use rbpf::EbpfVmMbuff;
+use riot_wrappers::ztimer::Clock;
+
+use super::analysis::{referenced_helper_ids, ProgramFormat};
+use super::helpers::register_all;
+use super::ALL_HELPERS;
+use crate::output::ResultSink;
+use crate::util::stats::Accumulator;
+use crate::util::time::Elapsed;
+
+/// Verifications per table, the mean is reported. The two alternate so that
+/// both see the same conditions.
+const ROUNDS: usize = 16;
+
+/// Time to verify `prog` with all helpers registered or none, `None` if it
+/// doesn't verify.
+fn verify_us(prog: &[u8], register: bool) -> Option<u32> {
+    let mut vm = EbpfVmMbuff::new(Some(prog), rbpf::InterpreterVariant::FemtoContainersHeader).ok()?;
+    if register {
+        register_all(&mut vm);
+    }
+    let mut verified = false;
+    let duration = Clock::usec()
+        .time(|| verified = vm.verify_loaded_program().is_ok())
+        .expect("failed to measure verification time")
+        .us();
+    if !verified {
+        return None;
+    }
+    Some(duration)
+}
+
+/// Reports the verification time with and without the helpers of each
+/// program which calls none.
+pub fn run(sink: &mut dyn ResultSink, programs: &[(&str, &[u8])]) {
+    for (name, prog) in programs {
+        match referenced_helper_ids(prog, ProgramFormat::FemtoContainersHeader) {
+            Some(ids) if ids.is_empty() => {}
+            Some(_) => {
+                sink.comment(&format!(
+                    "verify_registration program={} skipped it calls helpers, it doesn't verify without them",
+                    name
+                ));
+                continue;
+            }
+            None => {
+                sink.comment(&format!(
+                    "verify_registration program={} skipped its helper calls are unknown",
+                    name
+                ));
+                continue;
+            }
+        }
+        let (mut registered, mut empty) = (Accumulator::new(), Accumulator::new());
+        for _ in 0..ROUNDS {
+            match (verify_us(prog, true), verify_us(prog, false)) {
+                (Some(registered_us), Some(empty_us)) => {
+                    registered.add(registered_us);
+                    empty.add(empty_us);
+                }
+                _ => break,
+            }
+        }
+        let (registered_us, empty_us) = match (registered.mean(), empty.mean()) {
+            (Some(registered_us), Some(empty_us)) if registered.count() == ROUNDS as u32 => (registered_us, empty_us),
+            _ => {
+                sink.comment(&format!("verify_registration program={} rejected by the verifier", name));
+                continue;
+            }
+        };
+        let delta_ns = (registered_us as i64 - empty_us as i64) * 1000;
+        sink.comment(&format!(
+            "verify_registration program={} rounds={} helpers={} verify_registered_us={} verify_empty_us={} delta_ns_per_helper={}",
+            name,
+            ROUNDS,
+            ALL_HELPERS.len(),
+            registered_us,
+            empty_us,
+            delta_ns / ALL_HELPERS.len().max(1) as i64
+        ));
+    }
+}
@@ -0,0 +1,101 @@
+//! How fast the verifier rejects invalid programs, the path a loader takes
+//! for every bad program it receives. Each case is a short program that is
+//! well formed except for one defect the verifier checks for, built with the
+//! header of the program under benchmark like the verify_profile programs.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use rbpf::EbpfVmMbuff;
+use riot_wrappers::ztimer::Clock;
+
+use super::helpers::register_helpers;
+use super::ALL_HELPERS;
+use crate::output::ResultSink;
+use crate::util::stats::Accumulator;
+use crate::util::time::Elapsed;
+
+/// Rejections per case, the mean is reported.
+const ROUNDS: usize = 16;
+/// magic, version, flags, data_len, rodata_len, text_len, functions
+const FC_HEADER_SIZE: usize = 7 * 4;
+
+const MOV_R0_0: [u8; 8] = [0xb7, 0, 0, 0, 0, 0, 0, 0];
+const EXIT: [u8; 8] = [0x95, 0, 0, 0, 0, 0, 0, 0];
+
+/// (reason, text without the final `mov r0, 0; exit`, whether the final
+/// instructions are appended)
+const CASES: [(&str, &[[u8; 8]], bool); 7] = [
+    // mov r0, 0 without the exit
+    ("no_exit", &[MOV_R0_0], false),
+    // opcode 0xff is no instruction
+    ("unknown_opcode", &[[0xff, 0, 0, 0, 0, 0, 0, 0]], true),
+    // ja +100, past the end of the program
+    ("jump_out_of_bounds", &[[0x05, 0, 100, 0, 0, 0, 0, 0]], true),
+    // mov r10, 1, the frame pointer is read only
+    ("write_r10", &[[0xb7, 0x0a, 0, 0, 1, 0, 0, 0]], true),
+    // lddw r1, 1 without its second slot, the next instruction follows
+    ("incomplete_lddw", &[[0x18, 0x01, 0, 0, 1, 0, 0, 0]], true),
+    // div r1, 0
+    ("div_by_zero", &[[0x37, 0x01, 0, 0, 0, 0, 0, 0]], true),
+    // call of a helper id no helper has
+    ("unknown_helper", &[[0x85, 0, 0, 0, 0xff, 0xff, 0, 0]], true),
+];
+
+/// Builds the program of a case with the header fields of `template`.
+fn build(template: &[u8], text: &[[u8; 8]], terminated: bool) -> Vec<u8> {
+    let mut insns: Vec<u8> = text.iter().flatten().copied().collect();
+    if terminated {
+        insns.extend_from_slice(&MOV_R0_0);
+        insns.extend_from_slice(&EXIT);
+    }
+    let mut prog = Vec::with_capacity(FC_HEADER_SIZE + insns.len());
+    prog.extend_from_slice(&template[..12]);
+    // No data and rodata, the text and no functions
+    for field in [0, 0, insns.len() as u32, 0].iter() {
+        prog.extend_from_slice(&field.to_le_bytes());
+    }
+    prog.extend_from_slice(&insns);
+    prog
+}
+
+/// Mean time to reject the program with the error of the verifier, or why
+/// it couldn't be timed.
+fn reject_us(prog: &[u8]) -> Result<(u32, String), &'static str> {
+    let micro_sec = Clock::usec();
+    let mut times = Accumulator::new();
+    let mut error = String::new();
+    for _ in 0..ROUNDS {
+        let mut vm = EbpfVmMbuff::new(Some(prog), rbpf::InterpreterVariant::FemtoContainersHeader)
+            .map_err(|_| "refused by the loader before the verification")?;
+        register_helpers(&mut vm, &ALL_HELPERS);
+        let mut result = Ok(());
+        let duration = micro_sec
+            .time(|| result = vm.verify_loaded_program())
+            .expect("failed to measure verification time")
+            .us();
+        // The comment is `;` free, the error is quoted on a single line
+        let err = result.err().ok_or("not rejected by the verifier")?;
+        error = format!("{:?}", err).replace([';', '\n', '"'], " ");
+        times.add(duration);
+    }
+    Ok((times.mean().unwrap_or(0), error))
+}
+
+/// Reports the rejection time of each case. `template` is a program with the
+/// Femto-Container header whose header fields are reused.
+pub fn run(sink: &mut dyn ResultSink, template: &[u8]) {
+    if template.len() < FC_HEADER_SIZE {
+        sink.comment("verify_reject=skipped the program has no Femto-Container header to copy");
+        return;
+    }
+    for (code, (reason, text, terminated)) in CASES.iter().enumerate() {
+        match reject_us(&build(template, text, *terminated)) {
+            Ok((us, error)) => sink.comment(&format!(
+                "verify_reject reason={} code={} rounds={} reject_us={} error=\"{}\"",
+                reason, code, ROUNDS, us, error
+            )),
+            Err(why) => sink.comment(&format!("verify_reject reason={} code={} {}", reason, code, why)),
+        }
+    }
+}
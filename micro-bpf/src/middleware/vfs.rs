@@ -0,0 +1,155 @@
+//! File helpers backed by RIOT's VFS, for programs which process the contents
+//! of a file. A constfs with vfs/test.txt is mounted at /const.
+//!
+//! The helpers aren't part of ALL_HELPERS, their ids are not known to
+//! micro-bpf-common, `HELPERS` is registered with the raw ids instead. The
+//! only memory they accept is the program image (for paths) and the buffer
+//! passed to the program (see ebpf/vfs_read.c), as the VM's stack isn't
+//! visible outside of it. Failures return negative errno values.
+
+use portable_atomic::{AtomicI32, AtomicU64, AtomicUsize, Ordering};
+
+pub const VFS_OPEN_ID: u32 = 0x90;
+pub const VFS_READ_ID: u32 = 0x91;
+pub const VFS_CLOSE_ID: u32 = 0x92;
+
+pub const HELPERS: [(u32, fn(u64, u64, u64, u64, u64) -> u64); 3] = [
+    (VFS_OPEN_ID, bpf_vfs_open),
+    (VFS_READ_ID, bpf_vfs_read),
+    (VFS_CLOSE_ID, bpf_vfs_close),
+];
+
+static TEST_FILE: &[u8] = include_bytes!("../../vfs/test.txt");
+
+const EFAULT: i32 = 14;
+const EBADF: i32 = 9;
+const EMFILE: i32 = 24;
+const ENAMETOOLONG: i32 = 36;
+/// O_RDONLY of newlib and the native libc
+const O_RDONLY: i32 = 0;
+
+const MAX_PATH: usize = 64;
+/// Files a program may have open at once.
+const MAX_HANDLES: usize = 4;
+const CLOSED: i32 = -1;
+const CLOSED_HANDLE: AtomicI32 = AtomicI32::new(CLOSED);
+/// VFS file descriptor per handle of the program.
+static HANDLES: [AtomicI32; MAX_HANDLES] = [CLOSED_HANDLE; MAX_HANDLES];
+
+static PROGRAM_START: AtomicUsize = AtomicUsize::new(0);
+static PROGRAM_LEN: AtomicUsize = AtomicUsize::new(0);
+static BUFFER_START: AtomicUsize = AtomicUsize::new(0);
+static BUFFER_LEN: AtomicUsize = AtomicUsize::new(0);
+
+static BYTES_READ: AtomicU64 = AtomicU64::new(0);
+
+extern "C" {
+    fn bench_vfs_mount(data: *const u8, len: usize) -> i32;
+}
+
+/// Mounts the file system, once before the benchmark.
+pub fn mount() {
+    let result = unsafe { bench_vfs_mount(TEST_FILE.as_ptr(), TEST_FILE.len()) };
+    assert!(result == 0, "failed to mount the vfs test file system ({})", result);
+}
+
+pub fn is_vfs_helper(id: u32) -> bool {
+    HELPERS.iter().any(|(helper_id, _)| *helper_id == id)
+}
+
+/// Closes the files left open by the previous iteration and sets the memory
+/// the helpers accept for the next one.
+pub fn reset(program: &[u8], buffer: &[u8]) {
+    for handle in &HANDLES {
+        let fd = handle.swap(CLOSED, Ordering::Relaxed);
+        if fd != CLOSED {
+            unsafe { riot_sys::vfs_close(fd) };
+        }
+    }
+    PROGRAM_START.store(program.as_ptr() as usize, Ordering::Relaxed);
+    PROGRAM_LEN.store(program.len(), Ordering::Relaxed);
+    BUFFER_START.store(buffer.as_ptr() as usize, Ordering::Relaxed);
+    BUFFER_LEN.store(buffer.len(), Ordering::Relaxed);
+}
+
+/// Clears the byte counter, e.g. before the recorded iterations of a program.
+pub fn reset_bytes_read() {
+    BYTES_READ.store(0, Ordering::Relaxed);
+}
+
+pub fn bytes_read() -> u64 {
+    BYTES_READ.load(Ordering::Relaxed)
+}
+
+fn error(errno: i32) -> u64 {
+    -errno as i64 as u64
+}
+
+/// The region containing `len` bytes at `address`, as (start, length), or
+/// `None` if they are outside of the accepted memory.
+fn region_of(address: usize, len: usize, writable: bool) -> Option<(usize, usize)> {
+    let buffer = (BUFFER_START.load(Ordering::Relaxed), BUFFER_LEN.load(Ordering::Relaxed));
+    let program = (PROGRAM_START.load(Ordering::Relaxed), PROGRAM_LEN.load(Ordering::Relaxed));
+    let regions = if writable { &[buffer][..] } else { &[buffer, program][..] };
+    regions
+        .iter()
+        .copied()
+        .find(|(start, region_len)| address >= *start && address.checked_add(len).map_or(false, |end| end <= start + region_len))
+}
+
+fn fd_of(handle: u64) -> Result<i32, u64> {
+    match HANDLES.get(handle as usize).map(|fd| fd.load(Ordering::Relaxed)) {
+        Some(fd) if fd != CLOSED => Ok(fd),
+        _ => Err(error(EBADF)),
+    }
+}
+
+/// Opens the file at the NUL terminated path for reading, returns a handle.
+pub fn bpf_vfs_open(path_p: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
+    let address = path_p as usize;
+    let (start, len) = match region_of(address, 1, false) {
+        Some(region) => region,
+        None => return error(EFAULT),
+    };
+    // The terminator has to be within the same region
+    let available = (start + len - address).min(MAX_PATH);
+    let path = unsafe { core::slice::from_raw_parts(address as *const u8, available) };
+    if !path.contains(&0) {
+        return error(if available == MAX_PATH { ENAMETOOLONG } else { EFAULT });
+    }
+    let handle = match HANDLES.iter().position(|fd| fd.load(Ordering::Relaxed) == CLOSED) {
+        Some(handle) => handle,
+        None => return error(EMFILE),
+    };
+    let fd = unsafe { riot_sys::vfs_open(address as *const _, O_RDONLY, 0) };
+    if fd < 0 {
+        return fd as i64 as u64;
+    }
+    HANDLES[handle].store(fd, Ordering::Relaxed);
+    handle as u64
+}
+
+/// Reads up to `len` bytes into `buf_p`, returns the number of bytes read.
+pub fn bpf_vfs_read(handle: u64, buf_p: u64, len: u64, _a4: u64, _a5: u64) -> u64 {
+    let fd = match fd_of(handle) {
+        Ok(fd) => fd,
+        Err(error) => return error,
+    };
+    if region_of(buf_p as usize, len as usize, true).is_none() {
+        return error(EFAULT);
+    }
+    let read = unsafe { riot_sys::vfs_read(fd, buf_p as *mut _, len as _) };
+    if read > 0 {
+        BYTES_READ.fetch_add(read as u64, Ordering::Relaxed);
+    }
+    read as i64 as u64
+}
+
+pub fn bpf_vfs_close(handle: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64) -> u64 {
+    let fd = match fd_of(handle) {
+        Ok(fd) => fd,
+        Err(error) => return error,
+    };
+    HANDLES[handle as usize].store(CLOSED, Ordering::Relaxed);
+    unsafe { riot_sys::vfs_close(fd) as i64 as u64 }
+}
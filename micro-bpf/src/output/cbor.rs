@@ -0,0 +1,212 @@
+//! Results as CBOR records, for transports where CSV lines are too large or
+//! may arrive out of order. Every record is a map with integer keys:
+//!
+//! | key | value                                                    |
+//! |-----|----------------------------------------------------------|
+//! | 0   | sequence number, counting from 0, to detect lost records |
+//! | 1   | kind of the record, see below                            |
+//! | 2   | payload, depending on the kind                           |
+//!
+//! Kinds and their payload:
+//!
+//! * 0, metadata: map of text keys to text values. The metadata reported
+//!   before a header or row is sent as one record ahead of it.
+//! * 1, header: array of the column names.
+//! * 2, row: array with the values in the order of the last header. Values
+//!   which are not available are null.
+//! * 3, end: no payload, nothing follows.
+//!
+//! Banners and comments are meant for humans and not sent. For example the
+//! row `3;0;true` as the sixth record is `a3 00 05 01 02 02 83 03 00 f5`.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::{ResultSink, RowData, Transport, Value};
+use crate::infra::summary::ProgramSummary;
+use crate::util::cbor::Encoder;
+
+const SEQUENCE: u64 = 0;
+const KIND: u64 = 1;
+const PAYLOAD: u64 = 2;
+
+const METADATA: u64 = 0;
+const HEADER: u64 = 1;
+const ROW: u64 = 2;
+const END: u64 = 3;
+
+pub struct CborSink<T: Transport> {
+    transport: T,
+    sequence: u64,
+    /// Metadata not sent yet.
+    metadata: Vec<(String, String)>,
+}
+
+impl<T: Transport> CborSink<T> {
+    pub fn new(transport: T) -> Self {
+        CborSink {
+            transport,
+            sequence: 0,
+            metadata: Vec::new(),
+        }
+    }
+
+    /// Encodes the sequence number and kind, the caller adds the payload if
+    /// `payload` is set.
+    fn record(&mut self, kind: u64, payload: bool) -> Encoder {
+        let mut encoder = Encoder::new();
+        encoder
+            .map(if payload { 3 } else { 2 })
+            .uint(SEQUENCE)
+            .uint(self.sequence)
+            .uint(KIND)
+            .uint(kind);
+        if payload {
+            encoder.uint(PAYLOAD);
+        }
+        self.sequence += 1;
+        encoder
+    }
+
+    fn send(&mut self, encoder: Encoder) {
+        self.transport.send(&encoder.into_bytes());
+    }
+
+    fn flush_metadata(&mut self) {
+        if self.metadata.is_empty() {
+            return;
+        }
+        let metadata = core::mem::take(&mut self.metadata);
+        let mut encoder = self.record(METADATA, true);
+        encoder.map(metadata.len());
+        for (key, value) in &metadata {
+            encoder.text(key).text(value);
+        }
+        self.send(encoder);
+    }
+}
+
+impl<T: Transport> ResultSink for CborSink<T> {
+    fn banner(&mut self, _text: &str) {}
+
+    fn metadata(&mut self, key: &str, value: &str) {
+        self.metadata.push((key.to_string(), value.to_string()));
+    }
+
+    fn header(&mut self, columns: &[&str]) {
+        self.flush_metadata();
+        let mut encoder = self.record(HEADER, true);
+        encoder.array(columns.len());
+        for column in columns {
+            encoder.text(column);
+        }
+        self.send(encoder);
+    }
+
+    fn row(&mut self, row: &RowData) {
+        self.flush_metadata();
+        let mut encoder = self.record(ROW, true);
+        encoder.array(row.values.len());
+        for value in &row.values {
+            match value {
+                Value::UInt(value) => encoder.uint(*value),
+                Value::Int(value) => encoder.int(*value),
+                Value::Bool(value) => encoder.bool(*value),
+                Value::Str(value) => encoder.text(value),
+                Value::Na => encoder.null(),
+            };
+        }
+        self.send(encoder);
+    }
+
+    fn comment(&mut self, _text: &str) {}
+
+    fn text(&mut self, _line: &str) {}
+
+    fn summary(&mut self, _summary: &ProgramSummary) {}
+
+    fn finish(&mut self) {
+        self.flush_metadata();
+        let encoder = self.record(END, false);
+        self.send(encoder);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::cbor::decode::{item, Item};
+
+    /// Keeps the records instead of sending them.
+    impl Transport for Vec<Vec<u8>> {
+        fn send(&mut self, record: &[u8]) {
+            self.push(record.to_vec());
+        }
+    }
+
+    fn row() -> RowData<'static> {
+        let mut row = RowData::new();
+        row.push(Value::UInt(3));
+        row.push(Value::UInt(0));
+        row.push(Value::Bool(true));
+        row
+    }
+
+    #[test]
+    fn row_record_matches_the_documented_bytes() {
+        let mut sink = CborSink::new(Vec::new());
+        for _ in 0..6 {
+            sink.row(&row());
+        }
+        // The example of the module documentation, the sixth record
+        assert_eq!(sink.transport[5], [0xa3, 0x00, 0x05, 0x01, 0x02, 0x02, 0x83, 0x03, 0x00, 0xf5]);
+    }
+
+    #[test]
+    fn records_decode_field_by_field() {
+        let mut sink = CborSink::new(Vec::new());
+        sink.banner("Benchmark Begins");
+        sink.metadata("vm_kind", "mbuff");
+        sink.comment("not sent");
+        sink.header(&["iteration", "delta_us", "label"]);
+        let mut row = RowData::new();
+        row.push(Value::UInt(1000));
+        row.push(Value::Int(-42));
+        row.push(Value::Na);
+        sink.row(&row);
+        sink.finish();
+
+        let records: Vec<Item> = sink
+            .transport
+            .iter()
+            .map(|record| {
+                let (record, rest) = item(record);
+                assert!(rest.is_empty(), "bytes after the record");
+                record
+            })
+            .collect();
+        let text = |value: &str| Item::Text(value.to_string());
+        let field = |key: u64, value: Item| (Item::UInt(key), value);
+        assert_eq!(
+            records,
+            [
+                Item::Map(alloc::vec![
+                    field(SEQUENCE, Item::UInt(0)),
+                    field(KIND, Item::UInt(METADATA)),
+                    field(PAYLOAD, Item::Map(alloc::vec![(text("vm_kind"), text("mbuff"))])),
+                ]),
+                Item::Map(alloc::vec![
+                    field(SEQUENCE, Item::UInt(1)),
+                    field(KIND, Item::UInt(HEADER)),
+                    field(PAYLOAD, Item::Array(alloc::vec![text("iteration"), text("delta_us"), text("label")])),
+                ]),
+                Item::Map(alloc::vec![
+                    field(SEQUENCE, Item::UInt(2)),
+                    field(KIND, Item::UInt(ROW)),
+                    field(PAYLOAD, Item::Array(alloc::vec![Item::UInt(1000), Item::Int(-42), Item::Null])),
+                ]),
+                Item::Map(alloc::vec![field(SEQUENCE, Item::UInt(3)), field(KIND, Item::UInt(END))]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Sends one JSON record with the metadata and the program summaries to a
+//! CoAP server when the run ends, so that results can be collected without
+//! a serial capture:
+//!
+//! ```json
+//! {"metadata":[["rbpf_version","..."],["vm_kind","mbuff"],...],
+//!  "programs":[{"program":"benchmark","vm_kind":"mbuff","runs":5,...}]}
+//! ```
+//!
+//! The metadata is kept as pairs in the order it was reported, keys such as
+//! `vm_kind` repeat. The record is POSTed with nanocoap in a single request,
+//! it has to fit into one datagram. If the request fails the record is
+//! printed on the console as a `coap_report_json=` comment instead.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::{ResultSink, RowData, StdioSink};
+use crate::infra::summary::ProgramSummary;
+
+pub struct CoapReportSink {
+    uri: &'static str,
+    metadata: Vec<(String, String)>,
+    /// The summaries, already encoded as JSON objects.
+    programs: Vec<String>,
+    console: StdioSink,
+}
+
+/// Encodes a JSON string. `;` is escaped as well, the fallback comment must
+/// not contain the CSV separator.
+fn string(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len() + 2);
+    encoded.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            c if c < ' ' || c == ';' => encoded.push_str(&format!("\\u{:04x}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+    encoded
+}
+
+/// POSTs `payload` to `uri`, the error is the negative errno of nanocoap.
+fn post(uri: &str, payload: &[u8]) -> Result<(), isize> {
+    let mut url = Vec::with_capacity(uri.len() + 1);
+    url.extend_from_slice(uri.as_bytes());
+    url.push(0);
+    // Only the response code matters, its payload is dropped
+    let mut response = [0u8; 16];
+    let result = unsafe {
+        riot_sys::nanocoap_sock_post_url(
+            url.as_ptr() as _,
+            payload.as_ptr() as _,
+            payload.len() as _,
+            response.as_mut_ptr() as _,
+            response.len() as _,
+        )
+    };
+    if result < 0 {
+        Err(result as isize)
+    } else {
+        Ok(())
+    }
+}
+
+impl CoapReportSink {
+    pub fn new(uri: &'static str) -> Self {
+        CoapReportSink {
+            uri,
+            metadata: Vec::new(),
+            programs: Vec::new(),
+            console: StdioSink,
+        }
+    }
+
+    fn record(&self) -> String {
+        let metadata: Vec<String> = self
+            .metadata
+            .iter()
+            .map(|(key, value)| format!("[{},{}]", string(key), string(value)))
+            .collect();
+        format!(
+            "{{\"metadata\":[{}],\"programs\":[{}]}}",
+            metadata.join(","),
+            self.programs.join(",")
+        )
+    }
+}
+
+impl ResultSink for CoapReportSink {
+    fn banner(&mut self, _text: &str) {}
+
+    fn metadata(&mut self, key: &str, value: &str) {
+        self.metadata.push((key.to_string(), value.to_string()));
+    }
+
+    fn header(&mut self, _columns: &[&str]) {}
+
+    fn row(&mut self, _row: &RowData) {}
+
+    fn comment(&mut self, _text: &str) {}
+
+    fn text(&mut self, _line: &str) {}
+
+    fn summary(&mut self, summary: &ProgramSummary) {
+        let runs = summary.execution_time_us.count();
+        self.programs.push(format!(
+            "{{\"program\":{},\"vm_kind\":{},\"runs\":{},\"correct\":{},\"failed\":{},\
+             \"load_mean_us\":{},\"exec_mean_us\":{},\"exec_min_us\":{},\"exec_max_us\":{}}}",
+            string(&summary.name),
+            string(crate::infra::vm::current().name()),
+            runs,
+            summary.correct,
+            summary.failed,
+            summary.load_program_us.mean().unwrap_or(0),
+            summary.execution_time_us.mean().unwrap_or(0),
+            summary.execution_time_us.min().unwrap_or(0),
+            summary.execution_time_us.max().unwrap_or(0)
+        ));
+    }
+
+    fn finish(&mut self) {
+        let record = self.record();
+        match post(self.uri, record.as_bytes()) {
+            Ok(()) => self
+                .console
+                .comment(&format!("coap_report=sent uri={} bytes={}", self.uri, record.len())),
+            Err(error) => {
+                self.console.comment(&format!(
+                    "coap_report=failed uri={} error={}, the record follows",
+                    self.uri, error
+                ));
+                self.console.comment(&format!("coap_report_json={}", record));
+            }
+        }
+    }
+}
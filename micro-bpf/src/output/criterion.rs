@@ -0,0 +1,70 @@
+//! The execution times in the layout of criterion's raw.csv, written after
+//! the run for tooling built around criterion. Each program is a group, each
+//! variant and VM kind it ran with a function, each recorded iteration a row
+//! with its execution time in nanoseconds. The run label, if any, is the
+//! value. The lines contain no `;` and are skipped by the CSV parser.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::ResultSink;
+use crate::infra::config;
+use crate::infra::summary::ProgramSummary;
+
+const HEADER: &str = "group,function,value,sample_measured_value,iteration_count,unit";
+
+/// The samples of one program run with one VM kind.
+struct Entry {
+    group: String,
+    function: String,
+    execution_us: Vec<u32>,
+}
+
+/// Collects the samples until the end of the run.
+pub struct CriterionCsv {
+    entries: Vec<Entry>,
+}
+
+/// Keeps a field from splitting the row.
+fn field(value: &str) -> String {
+    value.replace(',', "_")
+}
+
+impl CriterionCsv {
+    pub fn new() -> Self {
+        CriterionCsv { entries: Vec::new() }
+    }
+
+    pub fn add(&mut self, summary: &ProgramSummary, variant: &str, vm_kind: &str) {
+        self.entries.push(Entry {
+            group: field(&summary.name),
+            function: field(&format!("{}_{}", variant, vm_kind)),
+            execution_us: summary.execution_samples.clone(),
+        });
+    }
+
+    pub fn write(&self, sink: &mut dyn ResultSink) {
+        // With exec-batch a sample covers all executions of the batch
+        #[cfg(feature = "exec-batch")]
+        let iteration_count = config::exec_batch();
+        #[cfg(not(feature = "exec-batch"))]
+        let iteration_count = 1;
+        let value = field(config::run_label());
+
+        sink.banner("Criterion CSV");
+        sink.text(HEADER);
+        for entry in self.entries.iter() {
+            for execution_us in entry.execution_us.iter() {
+                sink.text(&format!(
+                    "{},{},{},{},{},ns",
+                    entry.group,
+                    entry.function,
+                    value,
+                    *execution_us as u64 * 1000,
+                    iteration_count
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Delta encoding of the time columns, for slow serial links. The first row
+//! after each header carries the absolute load_program_us and
+//! execution_time_us, every later row the signed difference to the previous
+//! row of the column. The host reconstructs the times by a cumulative sum.
+//! A missing value stays "na" and doesn't move the base of its column.
+//!
+//! The scheme is announced once, as the `encoding` metadata after the first
+//! banner.
+
+use alloc::vec::Vec;
+
+use super::{ResultSink, RowData, Value};
+use crate::infra::summary::ProgramSummary;
+
+/// The encoded columns.
+const COLUMNS: &[&str] = &["load_program_us", "execution_time_us"];
+
+pub struct DeltaSink<S> {
+    inner: S,
+    announced: bool,
+    /// Index of each encoded column in the current header, with the last
+    /// value sent in it.
+    columns: Vec<(usize, Option<u64>)>,
+}
+
+impl<S: ResultSink> DeltaSink<S> {
+    pub fn new(inner: S) -> Self {
+        DeltaSink {
+            inner,
+            announced: false,
+            columns: Vec::new(),
+        }
+    }
+}
+
+impl<S: ResultSink> ResultSink for DeltaSink<S> {
+    fn banner(&mut self, text: &str) {
+        self.inner.banner(text);
+        if !self.announced {
+            self.announced = true;
+            self.inner.metadata("encoding", "delta columns=load_program_us,execution_time_us first=absolute");
+        }
+    }
+
+    fn metadata(&mut self, key: &str, value: &str) {
+        self.inner.metadata(key, value);
+    }
+
+    fn header(&mut self, columns: &[&str]) {
+        self.columns = columns
+            .iter()
+            .enumerate()
+            .filter(|(_, column)| COLUMNS.contains(column))
+            .map(|(i, _)| (i, None))
+            .collect();
+        self.inner.header(columns);
+    }
+
+    fn row(&mut self, row: &RowData) {
+        let mut encoded = RowData { values: row.values.clone() };
+        for (i, previous) in self.columns.iter_mut() {
+            if let Some(Value::UInt(value)) = row.values.get(*i).copied() {
+                if let Some(previous) = *previous {
+                    encoded.values[*i] = Value::Int(value as i64 - previous as i64);
+                }
+                *previous = Some(value);
+            }
+        }
+        self.inner.row(&encoded);
+    }
+
+    fn comment(&mut self, text: &str) {
+        self.inner.comment(text);
+    }
+
+    fn text(&mut self, line: &str) {
+        self.inner.text(line);
+    }
+
+    fn summary(&mut self, summary: &ProgramSummary) {
+        self.inner.summary(summary);
+    }
+
+    fn finish(&mut self) {
+        self.inner.finish();
+    }
+}
@@ -0,0 +1,131 @@
+//! Destinations of the benchmark results. Everything the benchmark reports
+//! goes through a `ResultSink`, so that transports are implemented apart from
+//! the measurement code.
+
+use alloc::vec::Vec;
+
+use crate::infra::summary::ProgramSummary;
+
+#[cfg(any(feature = "cbor-results", test))]
+mod cbor;
+#[cfg(feature = "coap-report")]
+mod coap;
+#[cfg(feature = "criterion-csv")]
+pub mod criterion;
+#[cfg(feature = "delta-encode")]
+mod delta;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+mod stdio;
+#[cfg(feature = "dual-uart")]
+mod uart;
+
+#[cfg(feature = "cbor-results")]
+pub use cbor::CborSink;
+#[cfg(feature = "coap-report")]
+pub use coap::CoapReportSink;
+#[cfg(feature = "delta-encode")]
+pub use delta::DeltaSink;
+pub use stdio::StdioSink;
+#[cfg(test)]
+pub use stdio::TextSink;
+#[cfg(feature = "dual-uart")]
+pub use uart::DualUartSink;
+
+/// A single field of a row.
+#[derive(Copy, Clone)]
+pub enum Value<'a> {
+    UInt(u64),
+    Int(i64),
+    Bool(bool),
+    Str(&'a str),
+    /// Not available, e.g. a timer that can't measure the value.
+    Na,
+}
+
+/// The fields of a row, in the order of the header columns.
+pub struct RowData<'a> {
+    pub values: Vec<Value<'a>>,
+}
+
+impl<'a> RowData<'a> {
+    pub fn new() -> Self {
+        RowData { values: Vec::new() }
+    }
+
+    pub fn push(&mut self, value: Value<'a>) {
+        self.values.push(value);
+    }
+}
+
+pub trait ResultSink {
+    /// Marks a section of the output, e.g. the begin and end of the
+    /// benchmark which the host tooling waits for.
+    fn banner(&mut self, text: &str);
+    /// A single configuration or result value.
+    fn metadata(&mut self, key: &str, value: &str);
+    /// Names of the row fields, sent before the rows they describe.
+    fn header(&mut self, columns: &[&str]);
+    fn row(&mut self, row: &RowData);
+    /// Free form information for humans.
+    fn comment(&mut self, text: &str);
+    /// A line of another text format, e.g. Prometheus metrics, written out
+    /// unchanged by the text sinks.
+    fn text(&mut self, line: &str);
+    /// The statistics of a program after its rows, for sinks which collect
+    /// them. The text sinks have them as comments already.
+    fn summary(&mut self, summary: &ProgramSummary);
+    /// Ends the output, after it nothing else is sent.
+    fn finish(&mut self);
+}
+
+/// Carries encoded records, e.g. as datagrams, for sinks with a binary format.
+pub trait Transport {
+    fn send(&mut self, record: &[u8]);
+}
+
+/// Sends everything to two sinks, e.g. to the serial console and the network.
+#[cfg_attr(not(any(feature = "cbor-results", feature = "coap-report")), allow(dead_code))]
+pub struct Tee<A, B>(pub A, pub B);
+
+impl<A: ResultSink, B: ResultSink> ResultSink for Tee<A, B> {
+    fn banner(&mut self, text: &str) {
+        self.0.banner(text);
+        self.1.banner(text);
+    }
+
+    fn metadata(&mut self, key: &str, value: &str) {
+        self.0.metadata(key, value);
+        self.1.metadata(key, value);
+    }
+
+    fn header(&mut self, columns: &[&str]) {
+        self.0.header(columns);
+        self.1.header(columns);
+    }
+
+    fn row(&mut self, row: &RowData) {
+        self.0.row(row);
+        self.1.row(row);
+    }
+
+    fn comment(&mut self, text: &str) {
+        self.0.comment(text);
+        self.1.comment(text);
+    }
+
+    fn text(&mut self, line: &str) {
+        self.0.text(line);
+        self.1.text(line);
+    }
+
+    fn summary(&mut self, summary: &ProgramSummary) {
+        self.0.summary(summary);
+        self.1.summary(summary);
+    }
+
+    fn finish(&mut self) {
+        self.0.finish();
+        self.1.finish();
+    }
+}
@@ -0,0 +1,107 @@
+//! The program summaries in the Prometheus text exposition format, written
+//! after the run for scrapers reading the serial log. The lines contain no
+//! `;` and are skipped by the CSV parser.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::ResultSink;
+use crate::infra::summary::ProgramSummary;
+use crate::util::stats::percentile;
+
+/// Summary of one program run with one VM kind.
+struct Entry {
+    labels: String,
+    execution_us: Vec<u32>,
+    execution_sum_us: u64,
+    correct: u32,
+    failed: u32,
+}
+
+/// Collects the summaries until the end of the run, the lines of a metric
+/// family have to be written together.
+pub struct Exposition {
+    entries: Vec<Entry>,
+}
+
+/// Escapes a label value, `\`, `"` and newlines need a backslash.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Exposition {
+    pub fn new() -> Self {
+        Exposition { entries: Vec::new() }
+    }
+
+    /// Adds a summary, labelled with the program, variant, VM kind and the
+    /// run label if there is one.
+    pub fn add(&mut self, summary: &ProgramSummary, variant: &str, vm_kind: &str) {
+        let mut labels = format!(
+            "program=\"{}\",variant=\"{}\",vm_kind=\"{}\"",
+            escape(&summary.name),
+            escape(variant),
+            escape(vm_kind)
+        );
+        let label = crate::infra::config::run_label();
+        if !label.is_empty() {
+            labels.push_str(&format!(",label=\"{}\"", escape(label)));
+        }
+        let runs = summary.execution_time_us.count();
+        self.entries.push(Entry {
+            labels,
+            execution_us: summary.execution_samples.clone(),
+            execution_sum_us: summary.execution_time_us.sum(),
+            correct: summary.correct,
+            failed: runs - summary.correct,
+        });
+    }
+
+    pub fn write(mut self, sink: &mut dyn ResultSink) {
+        sink.text("# HELP benchmark_execution_us Execution time of the program in microseconds.");
+        sink.text("# TYPE benchmark_execution_us summary");
+        for entry in self.entries.iter_mut() {
+            for (quantile, pct) in [("0.5", 50), ("0.95", 95)].iter() {
+                if let Some(value) = percentile(&mut entry.execution_us, *pct) {
+                    sink.text(&format!(
+                        "benchmark_execution_us{{{},quantile=\"{}\"}} {}",
+                        entry.labels, quantile, value
+                    ));
+                }
+            }
+            sink.text(&format!("benchmark_execution_us_sum{{{}}} {}", entry.labels, entry.execution_sum_us));
+            sink.text(&format!("benchmark_execution_us_count{{{}}} {}", entry.labels, entry.execution_us.len()));
+        }
+
+        sink.text("# HELP benchmark_execution_mean_us Mean execution time of the program in microseconds.");
+        sink.text("# TYPE benchmark_execution_mean_us gauge");
+        for entry in self.entries.iter() {
+            if !entry.execution_us.is_empty() {
+                let mean = entry.execution_sum_us / entry.execution_us.len() as u64;
+                sink.text(&format!("benchmark_execution_mean_us{{{}}} {}", entry.labels, mean));
+            }
+        }
+
+        sink.text("# HELP benchmark_iterations_correct_total Recorded iterations with the expected result.");
+        sink.text("# TYPE benchmark_iterations_correct_total counter");
+        for entry in self.entries.iter() {
+            sink.text(&format!("benchmark_iterations_correct_total{{{}}} {}", entry.labels, entry.correct));
+        }
+
+        sink.text("# HELP benchmark_iterations_failed_total Recorded iterations without the expected result.");
+        sink.text("# TYPE benchmark_iterations_failed_total counter");
+        for entry in self.entries.iter() {
+            sink.text(&format!("benchmark_iterations_failed_total{{{}}} {}", entry.labels, entry.failed));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! The default output: CSV rows and `#` prefixed metadata on stdio, as parsed
+//! by scripts/benchmark.py.
+
+use core::fmt::{self, Write};
+use riot_wrappers::stdio::Stdio;
+
+use super::{ResultSink, RowData, Transport, Value};
+use crate::infra::summary::ProgramSummary;
+use crate::print;
+
+pub struct StdioSink;
+
+/// The format of `StdioSink` written to any `Write`, StdioSink writes it to
+/// stdio and the tests to a buffer.
+pub struct TextSink<W: Write>(pub W);
+
+impl<W: Write> ResultSink for TextSink<W> {
+    fn banner(&mut self, text: &str) {
+        let _ = writeln!(self.0, "=== {} ===", text);
+    }
+
+    fn metadata(&mut self, key: &str, value: &str) {
+        let _ = writeln!(self.0, "# {}={}", key, value);
+    }
+
+    fn header(&mut self, columns: &[&str]) {
+        let _ = writeln!(self.0, "{}", columns.join(";"));
+    }
+
+    fn row(&mut self, row: &RowData) {
+        let _ = write_row(&mut self.0, row);
+    }
+
+    fn comment(&mut self, text: &str) {
+        let _ = writeln!(self.0, "# {}", text);
+    }
+
+    fn text(&mut self, line: &str) {
+        let _ = writeln!(self.0, "{}", line);
+    }
+
+    fn summary(&mut self, _summary: &ProgramSummary) {}
+
+    fn finish(&mut self) {
+        self.banner("Benchmark End");
+    }
+}
+
+impl ResultSink for StdioSink {
+    fn banner(&mut self, text: &str) {
+        TextSink(Stdio {}).banner(text);
+    }
+
+    fn metadata(&mut self, key: &str, value: &str) {
+        TextSink(Stdio {}).metadata(key, value);
+    }
+
+    fn header(&mut self, columns: &[&str]) {
+        TextSink(Stdio {}).header(columns);
+    }
+
+    fn row(&mut self, row: &RowData) {
+        TextSink(Stdio {}).row(row);
+    }
+
+    fn comment(&mut self, text: &str) {
+        TextSink(Stdio {}).comment(text);
+    }
+
+    fn text(&mut self, line: &str) {
+        TextSink(Stdio {}).text(line);
+    }
+
+    fn summary(&mut self, _summary: &ProgramSummary) {}
+
+    fn finish(&mut self) {
+        TextSink(Stdio {}).finish();
+    }
+}
+
+/// Writes the row as a `;` separated line, "na" for missing values.
+pub fn write_row(out: &mut impl Write, row: &RowData) -> fmt::Result {
+    for (i, value) in row.values.iter().enumerate() {
+        if i > 0 {
+            out.write_str(";")?;
+        }
+        match value {
+            Value::UInt(value) => write!(out, "{}", value)?,
+            Value::Int(value) => write!(out, "{}", value)?,
+            Value::Bool(value) => write!(out, "{}", value)?,
+            Value::Str(value) => out.write_str(value)?,
+            Value::Na => out.write_str("na")?,
+        }
+    }
+    out.write_str("\n")
+}
+
+/// Binary records as hex encoded comment lines, ignored by the CSV parser.
+impl Transport for StdioSink {
+    fn send(&mut self, record: &[u8]) {
+        print!("# cbor=");
+        for byte in record {
+            print!("{:02x}", byte);
+        }
+        print!("\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+    use crate::output::Tee;
+
+    /// The output of the calls of `f`, in the format of StdioSink.
+    fn render(f: impl FnOnce(&mut dyn ResultSink)) -> String {
+        let mut sink = TextSink(String::new());
+        f(&mut sink);
+        sink.0
+    }
+
+    fn row() -> RowData<'static> {
+        let mut row = RowData::new();
+        row.push(Value::UInt(0));
+        row.push(Value::Int(-12));
+        row.push(Value::Bool(true));
+        row.push(Value::Str("mbuff"));
+        row.push(Value::Na);
+        row
+    }
+
+    #[test]
+    fn metadata_line() {
+        assert_eq!(render(|sink| sink.metadata("rbpf_version", "0.2.0")), "# rbpf_version=0.2.0\n");
+    }
+
+    #[test]
+    fn header_line() {
+        assert_eq!(
+            render(|sink| sink.header(&["iteration", "load_program_us", "execution_time_us"])),
+            "iteration;load_program_us;execution_time_us\n"
+        );
+    }
+
+    #[test]
+    fn row_line() {
+        assert_eq!(render(|sink| sink.row(&row())), "0;-12;true;mbuff;na\n");
+    }
+
+    #[test]
+    fn banner_comment_and_end() {
+        let output = render(|sink| {
+            sink.banner("Benchmark Begins");
+            sink.comment("warmup=2 iterations discarded");
+            sink.text("<<iter 0>>");
+            sink.finish();
+        });
+        assert_eq!(
+            output,
+            "=== Benchmark Begins ===\n# warmup=2 iterations discarded\n<<iter 0>>\n=== Benchmark End ===\n"
+        );
+    }
+
+    #[test]
+    fn tee_writes_both_sinks_in_order() {
+        let mut tee = Tee(TextSink(String::new()), TextSink(String::new()));
+        tee.metadata("vm_kind", "mbuff");
+        tee.header(&["iteration", "correct"]);
+        tee.row(&row());
+        tee.finish();
+        let expected = "# vm_kind=mbuff\niteration;correct\n0;-12;true;mbuff;na\n=== Benchmark End ===\n";
+        assert_eq!(tee.0 .0, expected);
+        assert_eq!(tee.1 .0, expected);
+    }
+}
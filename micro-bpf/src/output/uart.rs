@@ -0,0 +1,112 @@
+//! Rows on a second UART, everything else on the console. The collector then
+//! reads a stream of nothing but the header and the rows, while the banners,
+//! metadata and comments can be followed on stdio.
+
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+
+use super::stdio::write_row;
+use super::{ResultSink, RowData, StdioSink};
+use crate::infra::summary::ProgramSummary;
+
+/// A UART initialized for writing only.
+struct Uart {
+    dev: riot_sys::uart_t,
+}
+
+impl Uart {
+    /// `None` if the UART doesn't exist or can't run at `baud`.
+    fn init(dev: u32, baud: u32) -> Option<Self> {
+        let dev = dev as riot_sys::uart_t;
+        // Without a receive callback the UART is only set up for writing
+        let status = unsafe { riot_sys::uart_init(dev, baud, None, core::ptr::null_mut()) };
+        if status == 0 {
+            Some(Uart { dev })
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Write for Uart {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        unsafe { riot_sys::uart_write(self.dev, s.as_ptr(), s.len() as _) };
+        Ok(())
+    }
+}
+
+pub struct DualUartSink {
+    console: StdioSink,
+    /// `None` if the UART couldn't be initialized, the rows stay on the
+    /// console then.
+    data: Option<Uart>,
+    dev: u32,
+    baud: u32,
+}
+
+impl DualUartSink {
+    pub fn new(dev: u32, baud: u32) -> Self {
+        DualUartSink {
+            console: StdioSink,
+            data: Uart::init(dev, baud),
+            dev,
+            baud,
+        }
+    }
+
+    /// The interfaces in use, for the metadata.
+    pub fn describe(&self) -> String {
+        match self.data {
+            Some(_) => format!("rows=UART_DEV({}) baud={} log=stdio", self.dev, self.baud),
+            None => format!(
+                "rows=stdio log=stdio, UART_DEV({}) failed to initialize at {} baud",
+                self.dev, self.baud
+            ),
+        }
+    }
+}
+
+impl ResultSink for DualUartSink {
+    fn banner(&mut self, text: &str) {
+        self.console.banner(text);
+    }
+
+    fn metadata(&mut self, key: &str, value: &str) {
+        self.console.metadata(key, value);
+    }
+
+    fn header(&mut self, columns: &[&str]) {
+        match &mut self.data {
+            Some(uart) => {
+                let _ = fmt::Write::write_fmt(uart, format_args!("{}\n", columns.join(";")));
+            }
+            None => self.console.header(columns),
+        }
+    }
+
+    fn row(&mut self, row: &RowData) {
+        match &mut self.data {
+            Some(uart) => {
+                let _ = write_row(uart, row);
+            }
+            None => self.console.row(row),
+        }
+    }
+
+    fn comment(&mut self, text: &str) {
+        self.console.comment(text);
+    }
+
+    fn text(&mut self, line: &str) {
+        self.console.text(line);
+    }
+
+    fn summary(&mut self, summary: &ProgramSummary) {
+        self.console.summary(summary);
+    }
+
+    fn finish(&mut self) {
+        self.console.finish();
+    }
+}
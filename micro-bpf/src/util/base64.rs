@@ -0,0 +1,58 @@
+//! Decoder for standard base64 (RFC 4648) with padding.
+
+use alloc::vec::Vec;
+
+pub enum Base64Error {
+    /// A character outside of the alphabet, at the given position.
+    InvalidCharacter { position: usize, character: u8 },
+    /// The length is not a multiple of 4, or `=` appears other than as the
+    /// last one or two characters.
+    InvalidPadding,
+}
+
+fn value(character: u8) -> Option<u8> {
+    match character {
+        b'A'..=b'Z' => Some(character - b'A'),
+        b'a'..=b'z' => Some(character - b'a' + 26),
+        b'0'..=b'9' => Some(character - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+pub fn decode(text: &[u8]) -> Result<Vec<u8>, Base64Error> {
+    if text.len() % 4 != 0 {
+        return Err(Base64Error::InvalidPadding);
+    }
+    let padding = text.iter().rev().take_while(|c| **c == b'=').count();
+    if padding > 2 {
+        return Err(Base64Error::InvalidPadding);
+    }
+
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+    for (index, chunk) in text.chunks(4).enumerate() {
+        let mut group: u32 = 0;
+        let mut len = 0;
+        for (offset, character) in chunk.iter().enumerate() {
+            let position = index * 4 + offset;
+            if *character == b'=' {
+                if position < text.len() - padding {
+                    return Err(Base64Error::InvalidPadding);
+                }
+                group <<= 6;
+                continue;
+            }
+            let value = value(*character).ok_or(Base64Error::InvalidCharacter {
+                position,
+                character: *character,
+            })?;
+            group = group << 6 | value as u32;
+            len += 1;
+        }
+        // 4 characters carry 3 bytes, 3 carry 2 and 2 carry 1
+        let group = group.to_be_bytes();
+        bytes.extend_from_slice(&group[1..len]);
+    }
+    Ok(bytes)
+}
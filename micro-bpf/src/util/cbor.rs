@@ -0,0 +1,244 @@
+//! Minimal CBOR (RFC 8949) encoder, just the types the result records need.
+//! Every item is encoded with the shortest head, maps and arrays have a
+//! definite length.
+
+use alloc::vec::Vec;
+
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const SIMPLE: u8 = 7;
+
+const FALSE: u8 = 20;
+const TRUE: u8 = 21;
+const NULL: u8 = 22;
+
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Encoder { bytes: Vec::new() }
+    }
+
+    fn head(&mut self, major: u8, value: u64) {
+        let major = major << 5;
+        if value < 24 {
+            self.bytes.push(major | value as u8);
+        } else if value <= u8::MAX as u64 {
+            self.bytes.push(major | 24);
+            self.bytes.push(value as u8);
+        } else if value <= u16::MAX as u64 {
+            self.bytes.push(major | 25);
+            self.bytes.extend_from_slice(&(value as u16).to_be_bytes());
+        } else if value <= u32::MAX as u64 {
+            self.bytes.push(major | 26);
+            self.bytes.extend_from_slice(&(value as u32).to_be_bytes());
+        } else {
+            self.bytes.push(major | 27);
+            self.bytes.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+
+    pub fn uint(&mut self, value: u64) -> &mut Self {
+        self.head(UNSIGNED, value);
+        self
+    }
+
+    pub fn int(&mut self, value: i64) -> &mut Self {
+        if value < 0 {
+            // -1 - value, which can't overflow in this form
+            self.head(NEGATIVE, !(value as u64));
+        } else {
+            self.head(UNSIGNED, value as u64);
+        }
+        self
+    }
+
+    pub fn bool(&mut self, value: bool) -> &mut Self {
+        self.head(SIMPLE, if value { TRUE } else { FALSE } as u64);
+        self
+    }
+
+    pub fn null(&mut self) -> &mut Self {
+        self.head(SIMPLE, NULL as u64);
+        self
+    }
+
+    pub fn text(&mut self, value: &str) -> &mut Self {
+        self.head(TEXT, value.len() as u64);
+        self.bytes.extend_from_slice(value.as_bytes());
+        self
+    }
+
+    /// Starts an array, followed by `len` items.
+    pub fn array(&mut self, len: usize) -> &mut Self {
+        self.head(ARRAY, len as u64);
+        self
+    }
+
+    /// Starts a map, followed by `len` pairs of key and value.
+    pub fn map(&mut self, len: usize) -> &mut Self {
+        self.head(MAP, len as u64);
+        self
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decoder of the items the encoder writes, for the tests to read records
+/// back.
+#[cfg(test)]
+pub mod decode {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use super::{FALSE, NULL, TRUE};
+
+    #[derive(Debug, PartialEq)]
+    pub enum Item {
+        UInt(u64),
+        Int(i64),
+        Text(String),
+        Array(Vec<Item>),
+        Map(Vec<(Item, Item)>),
+        Bool(bool),
+        Null,
+    }
+
+    /// Decodes the item at the start of `bytes`, returns it and the bytes
+    /// after it.
+    pub fn item(bytes: &[u8]) -> (Item, &[u8]) {
+        let (major, info, rest) = (bytes[0] >> 5, bytes[0] & 0x1f, &bytes[1..]);
+        let (value, mut rest) = match info {
+            0..=23 => (info as u64, rest),
+            24..=27 => {
+                let len = 1 << (info - 24);
+                let value = rest[..len].iter().fold(0u64, |value, byte| (value << 8) | *byte as u64);
+                (value, &rest[len..])
+            }
+            _ => panic!("indefinite or reserved length {}", info),
+        };
+        let item = match major {
+            0 => Item::UInt(value),
+            1 => Item::Int(-1 - value as i64),
+            3 => {
+                let (text, after) = rest.split_at(value as usize);
+                rest = after;
+                Item::Text(String::from_utf8(text.to_vec()).expect("text is not UTF-8"))
+            }
+            4 => {
+                let mut items = Vec::new();
+                for _ in 0..value {
+                    let (element, after) = item(rest);
+                    items.push(element);
+                    rest = after;
+                }
+                Item::Array(items)
+            }
+            5 => {
+                let mut pairs = Vec::new();
+                for _ in 0..value {
+                    let (key, after) = item(rest);
+                    let (value, after) = item(after);
+                    pairs.push((key, value));
+                    rest = after;
+                }
+                Item::Map(pairs)
+            }
+            7 => match value as u8 {
+                FALSE => Item::Bool(false),
+                TRUE => Item::Bool(true),
+                NULL => Item::Null,
+                simple => panic!("unexpected simple value {}", simple),
+            },
+            major => panic!("unexpected major type {}", major),
+        };
+        (item, rest)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode::{item, Item};
+    use super::*;
+
+    fn encoded(f: impl FnOnce(&mut Encoder) -> &mut Encoder) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        f(&mut encoder);
+        encoder.into_bytes()
+    }
+
+    #[test]
+    fn uint_uses_the_shortest_head() {
+        assert_eq!(encoded(|e| e.uint(0)), [0x00]);
+        assert_eq!(encoded(|e| e.uint(23)), [0x17]);
+        assert_eq!(encoded(|e| e.uint(24)), [0x18, 0x18]);
+        assert_eq!(encoded(|e| e.uint(255)), [0x18, 0xff]);
+        assert_eq!(encoded(|e| e.uint(256)), [0x19, 0x01, 0x00]);
+        assert_eq!(encoded(|e| e.uint(1_000_000)), [0x1a, 0x00, 0x0f, 0x42, 0x40]);
+        assert_eq!(
+            encoded(|e| e.uint(u64::MAX)),
+            [0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        );
+    }
+
+    #[test]
+    fn negative_int_encodes_minus_one_minus_the_value() {
+        assert_eq!(encoded(|e| e.int(-1)), [0x20]);
+        assert_eq!(encoded(|e| e.int(-24)), [0x37]);
+        assert_eq!(encoded(|e| e.int(-25)), [0x38, 0x18]);
+        assert_eq!(encoded(|e| e.int(-500)), [0x39, 0x01, 0xf3]);
+        assert_eq!(
+            encoded(|e| e.int(i64::MIN)),
+            [0x3b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        );
+        // Positive values are unsigned integers
+        assert_eq!(encoded(|e| e.int(10)), [0x0a]);
+    }
+
+    #[test]
+    fn text_is_length_prefixed_utf8() {
+        assert_eq!(encoded(|e| e.text("")), [0x60]);
+        assert_eq!(encoded(|e| e.text("IETF")), [0x64, 0x49, 0x45, 0x54, 0x46]);
+        let long = "execution_time_us_column";
+        let mut expected = alloc::vec![0x78, 24];
+        expected.extend_from_slice(long.as_bytes());
+        assert_eq!(encoded(|e| e.text(long)), expected);
+    }
+
+    #[test]
+    fn map_and_simple_values() {
+        assert_eq!(encoded(|e| e.map(0)), [0xa0]);
+        assert_eq!(
+            encoded(|e| e.map(2).uint(1).text("a").uint(2).array(3).bool(false).bool(true).null()),
+            [0xa2, 0x01, 0x61, 0x61, 0x02, 0x83, 0xf4, 0xf5, 0xf6]
+        );
+    }
+
+    #[test]
+    fn decode_reads_back_the_encoding() {
+        let bytes = encoded(|e| e.map(2).text("load").uint(70_000).text("delta").int(-300));
+        let (decoded, rest) = item(&bytes);
+        assert!(rest.is_empty());
+        assert_eq!(
+            decoded,
+            Item::Map(alloc::vec![
+                (Item::Text("load".into()), Item::UInt(70_000)),
+                (Item::Text("delta".into()), Item::Int(-300)),
+            ])
+        );
+    }
+}
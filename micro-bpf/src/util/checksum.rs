@@ -0,0 +1,5 @@
+/// 32 bit FNV-1a hash, cheap enough to detect changes of the program bytes.
+pub fn fnv1a(data: &[u8]) -> u32 {
+    data.iter()
+        .fold(0x811c_9dc5, |hash, byte| (hash ^ *byte as u32).wrapping_mul(0x0100_0193))
+}
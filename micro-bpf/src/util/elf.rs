@@ -0,0 +1,67 @@
+//! Minimal parser for 64-bit little-endian ELF object files. It only covers
+//! what is needed to inspect the sections of compiled eBPF programs.
+
+use alloc::vec::Vec;
+
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+/// Sections of this type occupy no space in the file (e.g. .bss).
+const SHT_NOBITS: u32 = 8;
+const SHF_EXECINSTR: u64 = 0x4;
+
+/// A single section of the object file.
+pub struct Section<'a> {
+    pub flags: u64,
+    pub data: &'a [u8],
+}
+
+impl Section<'_> {
+    /// Sections containing instructions, e.g. .text or named program sections.
+    pub fn is_executable(&self) -> bool {
+        self.flags & SHF_EXECINSTR != 0
+    }
+}
+
+/// Returns all sections of the object file, or None if it isn't a valid
+/// 64-bit little-endian ELF file.
+pub fn sections(obj: &[u8]) -> Option<Vec<Section<'_>>> {
+    if !obj.starts_with(ELF_MAGIC) || obj.get(4) != Some(&ELFCLASS64) || obj.get(5) != Some(&ELFDATA2LSB) {
+        return None;
+    }
+
+    let shoff = read_u64(obj, 0x28)? as usize;
+    let shentsize = read_u16(obj, 0x3a)? as usize;
+    let shnum = read_u16(obj, 0x3c)? as usize;
+
+    (0..shnum)
+        .map(|i| {
+            let header = shoff + i * shentsize;
+            let sh_type = read_u32(obj, header + 4)?;
+            let flags = read_u64(obj, header + 8)?;
+            let offset = read_u64(obj, header + 24)? as usize;
+            let size = read_u64(obj, header + 32)? as usize;
+            let data = if sh_type == SHT_NOBITS {
+                &[][..]
+            } else {
+                obj.get(offset..offset.checked_add(size)?)?
+            };
+            Some(Section { flags, data })
+        })
+        .collect()
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    let b = bytes.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let b = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    let b = bytes.get(offset..offset + 8)?;
+    Some(u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+}
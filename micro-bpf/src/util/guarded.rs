@@ -0,0 +1,185 @@
+//! Canary words around the memory a program may access, to detect writes
+//! past its bounds (e.g. by a JIT bug) right after the execution.
+//!
+//! With mbuff-guard, `MBUFF_GUARD_BYTES` bytes of a known pattern follow the
+//! inner value directly, before the canaries. A damaged guard doesn't abort
+//! the benchmark, it is reported per iteration in the guard_ok column.
+
+use core::mem::size_of;
+
+/// Canary words on each side of the guarded value.
+const CANARY_WORDS: usize = 4;
+const CANARY: u32 = 0xC0DE_CAFE;
+
+#[cfg(feature = "mbuff-guard")]
+include!(concat!(env!("OUT_DIR"), "/guard.rs"));
+/// Byte `i` of the guard is the pattern xor the low byte of `i`, so that a
+/// shifted copy of the guard doesn't pass for it.
+#[cfg(feature = "mbuff-guard")]
+const GUARD_PATTERN: u8 = 0xA5;
+
+#[cfg(feature = "mbuff-guard")]
+fn guard_byte(i: usize) -> u8 {
+    GUARD_PATTERN ^ i as u8
+}
+
+/// A value with canary words before and after it. Only the inner value may
+/// be exposed to the program.
+#[repr(C)]
+pub struct Guarded<T> {
+    head: [u32; CANARY_WORDS],
+    inner: T,
+    #[cfg(feature = "mbuff-guard")]
+    guard: [u8; GUARD_BYTES],
+    tail: [u32; CANARY_WORDS],
+}
+
+/// The first damaged canary, or guard byte with mbuff-guard.
+#[derive(Debug, PartialEq)]
+pub struct Damage {
+    /// Byte offset relative to the start of the inner value, negative before it.
+    pub offset: isize,
+    pub value: u32,
+}
+
+impl<T> Guarded<T> {
+    pub fn new(inner: T) -> Self {
+        Guarded {
+            head: [CANARY; CANARY_WORDS],
+            inner,
+            #[cfg(feature = "mbuff-guard")]
+            guard: {
+                let mut guard = [0u8; GUARD_BYTES];
+                for (i, byte) in guard.iter_mut().enumerate() {
+                    *byte = guard_byte(i);
+                }
+                guard
+            },
+            tail: [CANARY; CANARY_WORDS],
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// The inner value as the memory passed to the program.
+    pub fn bytes(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(&self.inner as *const T as *const u8, size_of::<T>()) }
+    }
+
+    /// Like `bytes`, for the memory of a program which writes to it.
+    pub fn bytes_mut(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(&mut self.inner as *mut T as *mut u8, size_of::<T>()) }
+    }
+
+    /// Whether the guard bytes right after the inner value still hold their
+    /// pattern.
+    #[cfg(feature = "mbuff-guard")]
+    pub fn guard_intact(&self) -> bool {
+        self.guard_damage().is_none()
+    }
+
+    /// The first guard byte which doesn't hold its pattern.
+    #[cfg(feature = "mbuff-guard")]
+    pub fn guard_damage(&self) -> Option<Damage> {
+        let inner = &self.inner as *const T as isize;
+        self.guard.iter().enumerate().find_map(|(i, byte)| {
+            let value = unsafe { core::ptr::read_volatile(byte) };
+            (value != guard_byte(i)).then(|| Damage {
+                offset: byte as *const u8 as isize - inner,
+                value: value as u32,
+            })
+        })
+    }
+
+    /// Checks all canaries, starting with the one closest to the inner value
+    /// on each side as overflows usually hit those first.
+    pub fn check(&self) -> Result<(), Damage> {
+        let inner = &self.inner as *const T as isize;
+        self.head
+            .iter()
+            .rev()
+            .chain(self.tail.iter())
+            .find_map(|word| {
+                // The program may have written through raw pointers, don't
+                // let the compiler assume the canaries are unchanged.
+                let value = unsafe { core::ptr::read_volatile(word) };
+                (value != CANARY).then(|| Damage {
+                    offset: word as *const u32 as isize - inner,
+                    value,
+                })
+            })
+            .map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Buffer = Guarded<[u32; 4]>;
+
+    fn offset_of<U>(guarded: &Buffer, field: &U) -> isize {
+        field as *const U as isize - guarded.inner() as *const [u32; 4] as isize
+    }
+
+    #[test]
+    fn untouched_buffer_passes() {
+        let mut guarded = Buffer::new([0; 4]);
+        // Writes within the inner value are allowed
+        *guarded.inner_mut() = [u32::MAX; 4];
+        assert_eq!(guarded.check(), Ok(()));
+        #[cfg(feature = "mbuff-guard")]
+        assert!(guarded.guard_intact());
+    }
+
+    #[test]
+    fn damaged_head_canary_is_reported() {
+        let mut guarded = Buffer::new([0; 4]);
+        guarded.head[0] = 0x1111_1111;
+        guarded.head[CANARY_WORDS - 2] = 0x2222_2222;
+        // The word closest to the inner value is reported first
+        let expected = Damage {
+            offset: offset_of(&guarded, &guarded.head[CANARY_WORDS - 2]),
+            value: 0x2222_2222,
+        };
+        assert_eq!(expected.offset, -8);
+        assert_eq!(guarded.check(), Err(expected));
+    }
+
+    #[test]
+    fn damaged_tail_canary_is_reported() {
+        let mut guarded = Buffer::new([0; 4]);
+        guarded.tail[1] = 0x3333_3333;
+        guarded.tail[3] = 0x4444_4444;
+        let expected = Damage {
+            offset: offset_of(&guarded, &guarded.tail[1]),
+            value: 0x3333_3333,
+        };
+        assert!(expected.offset >= size_of::<[u32; 4]>() as isize);
+        assert_eq!(guarded.check(), Err(expected));
+    }
+
+    #[cfg(feature = "mbuff-guard")]
+    #[test]
+    fn damaged_guard_byte_is_reported() {
+        let mut guarded = Buffer::new([0; 4]);
+        let last = GUARD_BYTES - 1;
+        guarded.guard[last] = !guard_byte(last);
+        guarded.guard[0] = !guard_byte(0);
+        // The guard follows the inner value directly
+        let expected = Damage {
+            offset: size_of::<[u32; 4]>() as isize,
+            value: !guard_byte(0) as u32,
+        };
+        assert!(!guarded.guard_intact());
+        assert_eq!(guarded.guard_damage(), Some(expected));
+        // The canaries are separate
+        assert_eq!(guarded.check(), Ok(()));
+    }
+}
@@ -1 +1,16 @@
+#[cfg(feature = "base64-load")]
+pub mod base64;
+#[cfg(any(feature = "cbor-results", feature = "dump-samples", test))]
+pub mod cbor;
+pub mod checksum;
+pub mod elf;
+pub mod guarded;
 pub mod hacks;
+#[cfg(feature = "scatter")]
+pub mod scatter;
+#[cfg(feature = "sparkline")]
+pub mod sparkline;
+pub mod stats;
+pub mod time;
+#[cfg(feature = "top-slowest")]
+pub mod top;
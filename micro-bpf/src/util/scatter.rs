@@ -0,0 +1,94 @@
+//! ASCII scatter plot of the load time (x) against the execution time (y) for
+//! the console. The samples are kept until the plot is rendered, as the
+//! ranges of both axes are only known then, and counted into a fixed grid of
+//! `WIDTH` x `HEIGHT` cells drawn by density.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const WIDTH: usize = 48;
+const HEIGHT: usize = 12;
+/// Samples kept per program, later ones are dropped.
+const MAX_SAMPLES: usize = 1024;
+/// Cells by the number of samples in them, the last one for all above.
+const DENSITY: [char; 5] = [' ', '.', ':', '*', '#'];
+
+pub struct Scatter {
+    samples: Vec<(u32, u32)>,
+}
+
+/// Range of one axis.
+#[derive(Copy, Clone)]
+pub struct Axis {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Axis {
+    fn of(values: impl Iterator<Item = u32> + Clone) -> Option<Self> {
+        Some(Axis {
+            min: values.clone().min()?,
+            max: values.max()?,
+        })
+    }
+
+    /// Cell of the value in `0..cells`.
+    fn cell(&self, value: u32, cells: usize) -> usize {
+        let range = (self.max - self.min) as u64;
+        if range == 0 {
+            return 0;
+        }
+        ((value - self.min) as u64 * (cells as u64 - 1) / range) as usize
+    }
+}
+
+/// The grid of a rendered plot, `rows` starts with the highest execution
+/// times.
+pub struct Plot {
+    pub x: Axis,
+    pub y: Axis,
+    /// Samples in the plot, at most `MAX_SAMPLES`.
+    pub samples: usize,
+    pub rows: Vec<String>,
+}
+
+impl Scatter {
+    pub fn new(total: usize) -> Self {
+        Scatter {
+            samples: Vec::with_capacity(total.min(MAX_SAMPLES)),
+        }
+    }
+
+    pub fn add(&mut self, load_us: u32, execution_us: u32) {
+        if self.samples.len() < MAX_SAMPLES {
+            self.samples.push((load_us, execution_us));
+        }
+    }
+
+    /// Returns the plot or `None` without samples.
+    pub fn render(&self) -> Option<Plot> {
+        let x = Axis::of(self.samples.iter().map(|sample| sample.0))?;
+        let y = Axis::of(self.samples.iter().map(|sample| sample.1))?;
+
+        let mut grid = [[0u8; WIDTH]; HEIGHT];
+        for (load, execution) in self.samples.iter() {
+            let cell = &mut grid[HEIGHT - 1 - y.cell(*execution, HEIGHT)][x.cell(*load, WIDTH)];
+            *cell = cell.saturating_add(1);
+        }
+
+        let rows = grid
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|count| DENSITY[(*count as usize).min(DENSITY.len() - 1)])
+                    .collect()
+            })
+            .collect();
+        Some(Plot {
+            x,
+            y,
+            samples: self.samples.len(),
+            rows,
+        })
+    }
+}
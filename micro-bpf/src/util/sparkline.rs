@@ -0,0 +1,51 @@
+//! One line plot of the execution times for the console. The samples are
+//! streamed into at most `WIDTH` buckets, each drawn as the block character
+//! of its mean within the min..max range of all bucket means.
+
+use alloc::string::String;
+
+use crate::util::stats::Accumulator;
+
+const WIDTH: usize = 64;
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub struct Sparkline {
+    buckets: [Accumulator; WIDTH],
+    /// Number of samples expected, spread evenly over the buckets.
+    total: usize,
+}
+
+impl Sparkline {
+    pub fn new(total: usize) -> Self {
+        Sparkline {
+            buckets: [Accumulator::new(); WIDTH],
+            total: total.max(1),
+        }
+    }
+
+    /// Adds the sample with the given index, counting from 0.
+    pub fn add(&mut self, index: usize, sample: u32) {
+        let bucket = (index * WIDTH / self.total).min(WIDTH - 1);
+        self.buckets[bucket].add(sample);
+    }
+
+    /// Returns the plot or `None` without samples.
+    pub fn render(&self) -> Option<String> {
+        let means = self.buckets.iter().filter_map(|bucket| bucket.mean());
+        let min = means.clone().min()?;
+        let max = means.clone().max()?;
+        let range = (max - min) as u64;
+        Some(
+            means
+                .map(|mean| {
+                    let level = if range == 0 {
+                        0
+                    } else {
+                        (mean - min) as u64 * (BLOCKS.len() as u64 - 1) / range
+                    };
+                    BLOCKS[level as usize]
+                })
+                .collect(),
+        )
+    }
+}
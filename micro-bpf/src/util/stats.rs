@@ -0,0 +1,357 @@
+//! Statistics over the timing samples collected by the benchmark.
+
+use alloc::vec::Vec;
+
+/// Running statistics over a stream of samples, without storing them.
+#[derive(Copy, Clone)]
+pub struct Accumulator {
+    count: u32,
+    sum: u64,
+    min: u32,
+    max: u32,
+}
+
+impl Accumulator {
+    pub const fn new() -> Self {
+        Accumulator {
+            count: 0,
+            sum: 0,
+            min: u32::MAX,
+            max: 0,
+        }
+    }
+
+    pub fn add(&mut self, sample: u32) {
+        self.count += 1;
+        self.sum += sample as u64;
+        self.min = self.min.min(sample);
+        self.max = self.max.max(sample);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    pub fn min(&self) -> Option<u32> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<u32> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    /// Integer mean of the samples, rounded down.
+    pub fn mean(&self) -> Option<u32> {
+        (self.count > 0).then(|| (self.sum / self.count as u64) as u32)
+    }
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Samples for the confidence interval, the normal approximation doesn't
+/// hold for fewer.
+#[cfg(feature = "confidence")]
+pub const CONFIDENCE_MIN_SAMPLES: u32 = 30;
+
+/// z value of a two sided 95% interval, squared.
+#[cfg(feature = "confidence")]
+const Z95_SQUARED: f64 = 1.96 * 1.96;
+
+/// Running mean and variance of the samples (Welford's algorithm), for the
+/// confidence interval of the mean.
+#[cfg(feature = "confidence")]
+#[derive(Copy, Clone)]
+pub struct Welford {
+    count: u32,
+    mean: f64,
+    /// Sum of the squared differences from the mean.
+    m2: f64,
+}
+
+#[cfg(feature = "confidence")]
+impl Welford {
+    pub const fn new() -> Self {
+        Welford {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    pub fn add(&mut self, sample: u32) {
+        let sample = sample as f64;
+        self.count += 1;
+        let delta = sample - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (sample - self.mean);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Square of the half-width of the 95% confidence interval of the mean,
+    /// from at least `CONFIDENCE_MIN_SAMPLES` samples.
+    fn half_width_squared(&self) -> Option<f64> {
+        if self.count < CONFIDENCE_MIN_SAMPLES {
+            return None;
+        }
+        let variance = self.m2 / (self.count - 1) as f64;
+        Some(Z95_SQUARED * variance / self.count as f64)
+    }
+
+    /// Half-width of the 95% confidence interval in nanoseconds, for samples
+    /// in microseconds.
+    pub fn half_width_ns(&self) -> Option<u64> {
+        self.half_width_squared().map(|squared| isqrt((squared * 1e6) as u64))
+    }
+
+    /// Half-width relative to the mean in hundredths of a percent.
+    pub fn half_width_centi_pct(&self) -> Option<u64> {
+        let mean_ns = (self.mean * 1000.0) as u64;
+        self.half_width_ns().map(|ns| ns * 10_000 / mean_ns.max(1))
+    }
+
+    /// Whether the half-width is at most `threshold_pct` percent of the mean.
+    /// Compared squared, no root is needed.
+    pub fn within(&self, threshold_pct: u32) -> bool {
+        let bound = threshold_pct as f64 * self.mean / 100.0;
+        self.half_width_squared().map_or(false, |squared| squared <= bound * bound)
+    }
+}
+
+/// Integer square root, rounded down.
+#[cfg(feature = "confidence")]
+fn isqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+    // Newton's iteration decreases from a start not below the root, this
+    // one can't overflow
+    let mut root = value / 2 + 1;
+    let mut next = (root + value / root) / 2;
+    while next < root {
+        root = next;
+        next = (root + value / root) / 2;
+    }
+    root
+}
+
+/// One run of the warmup as seen by `StabilityDetector::settle`.
+pub enum Run {
+    /// The run measured this sample.
+    Sample(u32),
+    /// The run was discarded without a sample.
+    Skipped,
+    /// No further run may happen, e.g. at the iteration cap.
+    Stop,
+}
+
+/// Detects when a sequence of samples has settled, i.e. when the spread
+/// (max - min) of the last `window` samples is within `threshold_pct` percent
+/// of their mean.
+pub struct StabilityDetector {
+    window: Vec<u32>,
+    size: usize,
+    next: usize,
+    threshold_pct: u32,
+}
+
+impl StabilityDetector {
+    pub fn new(size: usize, threshold_pct: u32) -> Self {
+        assert!(size > 0, "the stability window must hold at least one sample");
+        StabilityDetector {
+            window: Vec::with_capacity(size),
+            size,
+            next: 0,
+            threshold_pct,
+        }
+    }
+
+    /// Adds a sample and returns whether the samples are now stable.
+    pub fn add(&mut self, sample: u32) -> bool {
+        if self.window.len() < self.size {
+            self.window.push(sample);
+        } else {
+            self.window[self.next] = sample;
+        }
+        self.next = (self.next + 1) % self.size;
+        self.is_stable()
+    }
+
+    /// Takes runs from `next` until their samples are stable, at most `max`
+    /// of them. Returns the runs discarded and whether they became stable.
+    pub fn settle<E>(&mut self, max: usize, mut next: impl FnMut() -> Result<Run, E>) -> Result<(usize, bool), E> {
+        let mut discarded = 0;
+        let mut stable = false;
+        while !stable && discarded < max {
+            match next()? {
+                Run::Sample(sample) => stable = self.add(sample),
+                Run::Skipped => {}
+                Run::Stop => break,
+            }
+            discarded += 1;
+        }
+        Ok((discarded, stable))
+    }
+
+    pub fn is_stable(&self) -> bool {
+        if self.window.len() < self.size {
+            return false;
+        }
+        let mut acc = Accumulator::new();
+        self.window.iter().for_each(|s| acc.add(*s));
+        let (min, max, mean) = (acc.min().unwrap(), acc.max().unwrap(), acc.mean().unwrap());
+        (max - min) as u64 * 100 <= self.threshold_pct as u64 * mean as u64
+    }
+}
+
+/// Flags samples which exceed the mean of the first `window` samples by more
+/// than `threshold_pct` percent, e.g. when the board throttles during a long
+/// run.
+pub struct DriftDetector {
+    baseline: Accumulator,
+    window: u32,
+    threshold_pct: u32,
+    drifting: bool,
+    drifted: u32,
+}
+
+impl DriftDetector {
+    pub fn new(window: u32, threshold_pct: u32) -> Self {
+        assert!(window > 0, "the drift baseline must hold at least one sample");
+        DriftDetector {
+            baseline: Accumulator::new(),
+            window,
+            threshold_pct,
+            drifting: false,
+            drifted: 0,
+        }
+    }
+
+    /// Adds a sample and returns whether a drift starts with it, i.e. it
+    /// exceeds the baseline and the previous sample didn't.
+    pub fn add(&mut self, sample: u32) -> bool {
+        if self.baseline.count() < self.window {
+            self.baseline.add(sample);
+            return false;
+        }
+        let baseline = self.baseline.mean().unwrap() as u64;
+        let exceeds = sample as u64 * 100 > baseline * (100 + self.threshold_pct as u64);
+        self.drifted += exceeds as u32;
+        let starts = exceeds && !self.drifting;
+        self.drifting = exceeds;
+        starts
+    }
+
+    /// Mean of the first `window` samples, once there are that many.
+    pub fn baseline_us(&self) -> Option<u32> {
+        self.baseline.mean().filter(|_| self.baseline.count() == self.window)
+    }
+
+    /// Samples after the baseline which exceeded it.
+    pub fn drifted(&self) -> u32 {
+        self.drifted
+    }
+}
+
+/// Reduces consecutive samples in bursts of `size` to their maximum, the
+/// tail latency of each burst.
+#[cfg(feature = "burst")]
+pub struct BurstTails {
+    size: u32,
+    current: Accumulator,
+    tails: alloc::vec::Vec<u32>,
+}
+
+#[cfg(feature = "burst")]
+impl BurstTails {
+    pub fn new(size: u32) -> Self {
+        assert!(size > 0, "a burst must hold at least one sample");
+        BurstTails {
+            size,
+            current: Accumulator::new(),
+            tails: alloc::vec::Vec::new(),
+        }
+    }
+
+    /// Adds a sample, returns the tail of the burst it completes.
+    pub fn add(&mut self, sample: u32) -> Option<u32> {
+        self.current.add(sample);
+        if self.current.count() < self.size {
+            return None;
+        }
+        let tail = self.current.max();
+        self.current = Accumulator::new();
+        self.tails.extend(tail);
+        tail
+    }
+
+    /// Samples of the last burst, which isn't complete.
+    pub fn pending(&self) -> u32 {
+        self.current.count()
+    }
+
+    /// Tails of the completed bursts, in the order of the bursts.
+    pub fn tails(&self) -> &[u32] {
+        &self.tails
+    }
+}
+
+/// Nearest-rank percentile of `samples`, sorting them in place.
+pub fn percentile(samples: &mut [u32], pct: u32) -> Option<u32> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    let rank = (samples.len() * pct as usize + 99) / 100;
+    Some(samples[rank.max(1) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+
+    /// The Makefile defaults of WARMUP_WINDOW, WARMUP_SPREAD_PCT and WARMUP_MAX.
+    const WINDOW: usize = 5;
+    const SPREAD_PCT: u32 = 5;
+    const MAX: usize = 100;
+
+    fn settle(samples: impl IntoIterator<Item = u32>) -> (usize, bool) {
+        let mut samples = samples.into_iter();
+        let result: Result<_, Infallible> = StabilityDetector::new(WINDOW, SPREAD_PCT)
+            .settle(MAX, || Ok(samples.next().map_or(Run::Stop, Run::Sample)));
+        result.unwrap()
+    }
+
+    #[test]
+    fn settles_after_a_noisy_prefix() {
+        // The cold runs, then the window fills with samples within 5 %
+        let noisy = [900, 400, 650, 300];
+        let steady = [100; MAX];
+        assert_eq!(settle(noisy.iter().chain(&steady).copied()), (noisy.len() + WINDOW, true));
+    }
+
+    #[test]
+    fn stops_at_the_cap_without_settling() {
+        let alternating = (0..).map(|i| if i % 2 == 0 { 100 } else { 200 });
+        assert_eq!(settle(alternating), (MAX, false));
+    }
+
+    #[test]
+    fn skipped_runs_count_as_discarded() {
+        let mut runs = core::iter::once(Run::Skipped).chain(core::iter::repeat_with(|| Run::Sample(100)));
+        let result: Result<_, Infallible> =
+            StabilityDetector::new(WINDOW, SPREAD_PCT).settle(MAX, || Ok(runs.next().unwrap()));
+        assert_eq!(result.unwrap(), (1 + WINDOW, true));
+    }
+}
@@ -0,0 +1,124 @@
+use riot_wrappers::ztimer::{Clock, Ticks};
+
+/// Durations measured with a ztimer clock. The rows are in microseconds,
+/// ticks of a clock with another frequency are converted instead of being
+/// taken as microseconds.
+pub trait Elapsed {
+    /// Frequency of the clock that counted the ticks.
+    fn hz(&self) -> u32;
+    /// The duration in picoseconds, the conversions below go through it.
+    fn ps(&self) -> u64;
+
+    /// The duration in microseconds, truncated.
+    fn us(&self) -> u32 {
+        (self.ps() / 1_000_000) as u32
+    }
+
+    /// The duration in nanoseconds, truncated.
+    fn ns(&self) -> u64 {
+        self.ps() / 1000
+    }
+}
+
+impl<const HZ: u32> Elapsed for Ticks<HZ> {
+    fn hz(&self) -> u32 {
+        HZ
+    }
+
+    fn ps(&self) -> u64 {
+        (self.0 as u64).saturating_mul(tick_ps(HZ))
+    }
+}
+
+/// Frequency of `clock` as given by its type, i.e. fixed at compile time. The
+/// board's configuration is not read back at runtime.
+pub fn clock_hz<const HZ: u32>(_clock: &Clock<HZ>) -> u32 {
+    HZ
+}
+
+/// Length of a tick of a clock at `hz`, in picoseconds to keep the
+/// fraction of clocks that don't divide a second into whole nanoseconds.
+pub fn tick_ps(hz: u32) -> u64 {
+    1_000_000_000_000 / hz.max(1) as u64
+}
+
+/// Returns the current time of the microsecond ztimer. Used where a timed
+/// closure doesn't fit, e.g. for timestamps taken across function boundaries.
+pub fn now_us() -> u32 {
+    unsafe {
+        // The cast is needed because riot_sys::ZTIMER_USEC is a
+        // riot_sys::ztimer_clock_t while the inline function expects the type
+        // from the inline module.
+        riot_sys::inline::ztimer_now(riot_sys::ZTIMER_USEC as *mut riot_sys::inline::ztimer_clock_t)
+    }
+}
+
+/// Raw ticks of the peripheral timer behind ZTIMER_USEC, which on many boards
+/// runs faster than 1 MHz. ztimer itself only offers microseconds.
+#[cfg(feature = "ns-precision")]
+pub mod ns {
+    use portable_atomic::{AtomicU32, Ordering};
+
+    /// The timer ZTIMER_USEC is configured on by default (TIMER_DEV(0)).
+    const TIMER_DEV: riot_sys::tim_t = 0;
+    /// Length of the calibration against ZTIMER_USEC. Short enough that a
+    /// 16 bit timer at 64 MHz doesn't wrap.
+    const CALIBRATION_US: u32 = 1000;
+    /// Detected frequency, 0 if not detected yet and 1 if it is no finer than
+    /// a microsecond.
+    static TIMER_HZ: AtomicU32 = AtomicU32::new(0);
+
+    pub fn ticks() -> u32 {
+        unsafe { riot_sys::timer_read(TIMER_DEV) as u32 }
+    }
+
+    /// Frequency of the timer if it resolves below a microsecond, measured
+    /// against ZTIMER_USEC on first use.
+    pub fn timer_hz() -> Option<u32> {
+        let mut hz = TIMER_HZ.load(Ordering::Relaxed);
+        if hz == 0 {
+            let (start_us, start) = (super::now_us(), ticks());
+            unsafe { riot_sys::ztimer_sleep(riot_sys::ZTIMER_USEC, CALIBRATION_US) };
+            let (elapsed_us, elapsed) = (super::now_us().wrapping_sub(start_us), ticks().wrapping_sub(start));
+            hz = ((elapsed as u64 * 1_000_000) / elapsed_us.max(1) as u64) as u32;
+            if hz <= 1_000_000 {
+                hz = 1;
+            }
+            TIMER_HZ.store(hz, Ordering::Relaxed);
+        }
+        (hz > 1).then(|| hz)
+    }
+
+    /// Nanoseconds between two tick readings. `elapsed_us` is the same
+    /// interval measured by ZTIMER_USEC, if both disagree by more than the
+    /// calibration error the timer wrapped (e.g. a 16 bit timer) and the
+    /// result is discarded.
+    pub fn elapsed_ns(start: u32, end: u32, elapsed_us: u32) -> Option<u64> {
+        let hz = timer_hz()? as u64;
+        let ns = end.wrapping_sub(start) as u64 * 1_000_000_000 / hz;
+        let diff_us = (ns / 1000) as i64 - elapsed_us as i64;
+        (diff_us.abs() <= 2 + elapsed_us as i64 / 100).then(|| ns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn microsecond_ticks_convert_exactly() {
+        let ticks = Ticks::<1_000_000>(u32::MAX);
+        assert_eq!(ticks.us(), u32::MAX);
+        assert_eq!(ticks.ns(), u32::MAX as u64 * 1000);
+    }
+
+    #[test]
+    fn ticks_of_another_clock_go_through_the_tick_length() {
+        // 32768 Hz has a tick of 30517.578125 ns, truncated to whole ps
+        assert_eq!(tick_ps(32_768), 30_517_578);
+        let ticks = Ticks::<32_768>(32_768);
+        assert_eq!(ticks.ns(), 999_999_995);
+        assert_eq!(ticks.us(), 999_999);
+        assert_eq!(Ticks::<1000>(3).us(), 3000);
+    }
+}
@@ -0,0 +1,82 @@
+//! Keeps the `capacity` largest samples of a stream with their index, without
+//! storing the others. An insertion replaces the smallest kept sample, which
+//! is found by a scan, the capacity is meant to be small.
+
+use alloc::vec::Vec;
+
+pub struct TopN {
+    capacity: usize,
+    /// (index, sample), unordered.
+    entries: Vec<(usize, u32)>,
+}
+
+impl TopN {
+    pub fn new(capacity: usize) -> Self {
+        TopN {
+            capacity,
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Adds the sample with the given index. On a tie the earlier sample is
+    /// kept.
+    pub fn add(&mut self, index: usize, sample: u32) {
+        if self.entries.len() < self.capacity {
+            self.entries.push((index, sample));
+            return;
+        }
+        let smallest = self
+            .entries
+            .iter_mut()
+            .min_by_key(|(index, sample)| (*sample, core::cmp::Reverse(*index)));
+        if let Some(smallest) = smallest {
+            if sample > smallest.1 {
+                *smallest = (index, sample);
+            }
+        }
+    }
+
+    /// The kept samples, the largest first and equal ones by index.
+    pub fn sorted(&self) -> Vec<(usize, u32)> {
+        let mut sorted = self.entries.clone();
+        sorted.sort_unstable_by_key(|(index, sample)| (core::cmp::Reverse(*sample), *index));
+        sorted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn top(capacity: usize, samples: &[u32]) -> Vec<(usize, u32)> {
+        let mut top = TopN::new(capacity);
+        samples.iter().enumerate().for_each(|(index, sample)| top.add(index, *sample));
+        top.sorted()
+    }
+
+    #[test]
+    fn keeps_all_of_fewer_samples_than_the_capacity() {
+        assert_eq!(top(5, &[3, 9, 1]), vec![(1, 9), (0, 3), (2, 1)]);
+        assert_eq!(top(5, &[]), vec![]);
+    }
+
+    #[test]
+    fn evicts_the_smallest_sample() {
+        assert_eq!(top(3, &[5, 1, 8, 7, 2, 6]), vec![(2, 8), (3, 7), (5, 6)]);
+    }
+
+    #[test]
+    fn keeps_the_earlier_of_equal_samples() {
+        assert_eq!(top(2, &[4, 4, 4, 4]), vec![(0, 4), (1, 4)]);
+        // An equal sample evicts nothing, a tie at the bottom keeps the earlier
+        assert_eq!(top(2, &[9, 4, 4, 9]), vec![(0, 9), (3, 9)]);
+        assert_eq!(top(3, &[9, 4, 6, 4]), vec![(0, 9), (2, 6), (1, 4)]);
+    }
+
+    #[test]
+    fn sorted_is_descending() {
+        let sorted = top(4, &[2, 7, 3, 11, 5, 13, 1]);
+        assert_eq!(sorted, vec![(5, 13), (3, 11), (1, 7), (4, 5)]);
+        assert!(sorted.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+}
@@ -0,0 +1,39 @@
+/*
+ * Read-only file system of the vfs-helpers feature, its single file is
+ * embedded by the Rust code (src/middleware/vfs.rs).
+ */
+
+#ifdef MODULE_CONSTFS
+
+#include <errno.h>
+
+#include "fs/constfs.h"
+#include "vfs.h"
+
+static constfs_file_t files[] = {
+    {
+        .path = "/test.txt",
+        .size = 0,
+        .data = NULL,
+    },
+};
+
+static constfs_t fs_desc = {
+    .nfiles = sizeof(files) / sizeof(files[0]),
+    .files = files,
+};
+
+static vfs_mount_t mount = {
+    .fs = &constfs_file_system,
+    .mount_point = "/const",
+    .private_data = &fs_desc,
+};
+
+int bench_vfs_mount(const void *data, size_t len)
+{
+    files[0].data = data;
+    files[0].size = len;
+    return vfs_mount(&mount);
+}
+
+#endif /* MODULE_CONSTFS */
@@ -0,0 +1,62 @@
+#!/usr/bin/env python3
+"""Signs benchmark programs for the signed-programs feature of micro-bpf.
+
+    sign_program.py keygen signing.key   # writes signing.key and signing.pub
+    sign_program.py sign signing.key micro-bpf/benchmark.bin
+
+The signature is written next to the program as <program>.sig (64 raw bytes),
+the public key as 32 raw bytes. build.rs embeds both.
+"""
+
+import argparse
+import sys
+from pathlib import Path
+
+from cryptography.hazmat.primitives import serialization
+from cryptography.hazmat.primitives.asymmetric.ed25519 import Ed25519PrivateKey
+
+
+def keygen(key_path: Path) -> None:
+    key = Ed25519PrivateKey.generate()
+    key_path.write_bytes(key.private_bytes(
+        serialization.Encoding.Raw,
+        serialization.PrivateFormat.Raw,
+        serialization.NoEncryption(),
+    ))
+    public_path = key_path.with_suffix(".pub")
+    public_path.write_bytes(key.public_key().public_bytes(
+        serialization.Encoding.Raw,
+        serialization.PublicFormat.Raw,
+    ))
+    print(f"Wrote {key_path} and {public_path}")
+
+
+def sign(key_path: Path, programs: list[Path]) -> None:
+    key = Ed25519PrivateKey.from_private_bytes(key_path.read_bytes())
+    for program in programs:
+        signature_path = program.with_name(program.name + ".sig")
+        signature_path.write_bytes(key.sign(program.read_bytes()))
+        print(f"Signed {program} -> {signature_path}")
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Sign micro-bpf benchmark programs with Ed25519.")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+
+    keygen_parser = subparsers.add_parser("keygen", help="Generate a new key pair.")
+    keygen_parser.add_argument("key", type=Path, help="Private key file, the public key is written next to it as .pub")
+
+    sign_parser = subparsers.add_parser("sign", help="Sign programs.")
+    sign_parser.add_argument("key", type=Path, help="Private key file written by keygen")
+    sign_parser.add_argument("programs", type=Path, nargs="+", help="Programs (.bin or .o) to sign")
+
+    args = parser.parse_args()
+    if args.command == "keygen":
+        keygen(args.key)
+    else:
+        sign(args.key, args.programs)
+    return 0
+
+
+if __name__ == '__main__':
+    sys.exit(main())